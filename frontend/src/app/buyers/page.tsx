@@ -5,7 +5,7 @@ import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
 import { Button } from '@/components/ui/button'
 import { api } from '@/lib/api'
-import { Buyer, CreateBuyerRequest } from '@/types'
+import { Buyer, Page, CreateBuyerRequest } from '@/types'
 import { formatDate } from '@/lib/utils'
 import { 
   Plus, 
@@ -28,8 +28,8 @@ export default function BuyersPage() {
   const { data: buyers, isLoading, error } = useQuery({
     queryKey: ['buyers'],
     queryFn: async () => {
-      const response = await api.get<Buyer[]>('/buyers')
-      return response.data
+      const response = await api.get<Page<Buyer>>('/buyers', { params: { per_page: 500 } })
+      return response.data.items
     },
   })
 
@@ -6,7 +6,7 @@ import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
 import { Button } from '@/components/ui/button'
 import { api } from '@/lib/api'
-import { InventoryPart, CreateInventoryPartRequest } from '@/types'
+import { InventoryPart, Page, CreateInventoryPartRequest } from '@/types'
 import { formatCurrency } from '@/lib/utils'
 import { 
   Plus, 
@@ -33,8 +33,8 @@ export default function InventoryPage() {
   const { data: parts, isLoading, error } = useQuery({
     queryKey: ['inventory'],
     queryFn: async () => {
-      const response = await api.get<InventoryPart[]>('/inventory')
-      return response.data
+      const response = await api.get<Page<InventoryPart>>('/inventory', { params: { per_page: 500 } })
+      return response.data.items
     },
   })
 
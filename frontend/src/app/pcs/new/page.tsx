@@ -2,11 +2,11 @@
 
 import { useState } from 'react'
 import { useRouter } from 'next/navigation'
-import { useMutation, useQueryClient } from '@tanstack/react-query'
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
 import { Button } from '@/components/ui/button'
 import { api } from '@/lib/api'
-import { CreatePcRequest, ComponentType } from '@/types'
+import { CreatePcRequest, ComponentCategory, ComponentType } from '@/types'
 import { ArrowLeft, Plus, Trash2 } from 'lucide-react'
 
 interface Component {
@@ -17,22 +17,16 @@ interface Component {
   notes?: string
 }
 
-const componentTypes: { value: ComponentType; label: string }[] = [
-  { value: 'Cpu', label: 'CPU' },
-  { value: 'Gpu', label: 'GPU' },
-  { value: 'Motherboard', label: 'Motherboard' },
-  { value: 'Ram', label: 'RAM' },
-  { value: 'Storage1', label: 'Primary Storage' },
-  { value: 'Storage2', label: 'Secondary Storage' },
-  { value: 'Psu', label: 'Power Supply' },
-  { value: 'Case', label: 'Case' },
-  { value: 'CpuCooler', label: 'CPU Cooler' },
-  { value: 'Additional', label: 'Additional' },
-]
-
 export default function BuildPcPage() {
   const router = useRouter()
   const queryClient = useQueryClient()
+  const { data: categories = [] } = useQuery({
+    queryKey: ['component-categories'],
+    queryFn: async () => {
+      const response = await api.get<ComponentCategory[]>('/component-categories')
+      return response.data
+    }
+  })
   
   const [pcName, setPcName] = useState('')
   const [buildDate, setBuildDate] = useState('')
@@ -41,7 +35,7 @@ export default function BuildPcPage() {
   const [components, setComponents] = useState<Component[]>([
     {
       id: '1',
-      component_type: 'Cpu',
+      component_type: 'cpu',
       component_name: '',
       cost: 0,
       notes: ''
@@ -65,7 +59,7 @@ export default function BuildPcPage() {
   const addComponent = () => {
     const newComponent: Component = {
       id: Date.now().toString(),
-      component_type: 'Cpu',
+      component_type: 'cpu',
       component_name: '',
       cost: 0,
       notes: ''
@@ -252,9 +246,9 @@ export default function BuildPcPage() {
                           onChange={(e) => updateComponent(component.id, 'component_type', e.target.value as ComponentType)}
                           className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
                         >
-                          {componentTypes.map(type => (
-                            <option key={type.value} value={type.value}>
-                              {type.label}
+                          {categories.map(category => (
+                            <option key={category.slug} value={category.slug}>
+                              {category.name}
                             </option>
                           ))}
                         </select>
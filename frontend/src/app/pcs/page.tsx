@@ -5,7 +5,7 @@ import { useQuery } from '@tanstack/react-query'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
 import { Button } from '@/components/ui/button'
 import { api } from '@/lib/api'
-import { Pc } from '@/types'
+import { Page, Pc } from '@/types'
 import { formatCurrency, formatDate } from '@/lib/utils'
 import { Plus, Computer, TrendingUp, Clock, DollarSign } from 'lucide-react'
 
@@ -13,8 +13,8 @@ export default function PcsPage() {
   const { data: pcs, isLoading, error } = useQuery({
     queryKey: ['pcs'],
     queryFn: async () => {
-      const response = await api.get<Pc[]>('/pcs')
-      return response.data
+      const response = await api.get<Page<Pc>>('/pcs', { params: { per_page: 500 } })
+      return response.data.items
     },
   })
 
@@ -3,7 +3,7 @@
 import { useQuery } from '@tanstack/react-query'
 import { api } from '@/lib/api'
 import { Card } from '@/components/ui/card'
-import { Pc } from '@/types'
+import { Page, Pc } from '@/types'
 import { formatCurrency } from '@/lib/utils'
 import { Computer, DollarSign, TrendingUp, Clock } from 'lucide-react'
 
@@ -11,8 +11,8 @@ export function DashboardStats() {
   const { data: pcs, isLoading } = useQuery({
     queryKey: ['pcs'],
     queryFn: async () => {
-      const response = await api.get<Pc[]>('/pcs')
-      return response.data
+      const response = await api.get<Page<Pc>>('/pcs', { params: { per_page: 500 } })
+      return response.data.items
     },
   })
 
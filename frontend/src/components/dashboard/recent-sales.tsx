@@ -3,15 +3,15 @@
 import { useQuery } from '@tanstack/react-query'
 import { api } from '@/lib/api'
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/card'
-import { Pc } from '@/types'
+import { Page, Pc } from '@/types'
 import { formatCurrency, formatDate } from '@/lib/utils'
 
 export function RecentSales() {
   const { data: pcs, isLoading } = useQuery({
     queryKey: ['pcs'],
     queryFn: async () => {
-      const response = await api.get<Pc[]>('/pcs')
-      return response.data
+      const response = await api.get<Page<Pc>>('/pcs', { params: { per_page: 500 } })
+      return response.data.items
     },
   })
 
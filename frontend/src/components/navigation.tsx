@@ -1,15 +1,17 @@
 'use client'
 
 import Link from 'next/link'
-import { usePathname } from 'next/navigation'
+import { usePathname, useRouter } from 'next/navigation'
 import { cn } from '@/lib/utils'
+import { api, clearToken } from '@/lib/api'
 import { 
   Home, 
   Computer, 
   Package, 
   Users, 
   BarChart3,
-  Plus
+  Plus,
+  LogOut
 } from 'lucide-react'
 
 const navigation = [
@@ -23,6 +25,20 @@ const navigation = [
 
 export function Navigation() {
   const pathname = usePathname()
+  const router = useRouter()
+
+  const logout = async () => {
+    try {
+      await api.post('/auth/logout')
+    } finally {
+      clearToken()
+      router.push('/login')
+    }
+  }
+
+  if (pathname === '/login') {
+    return null
+  }
 
   return (
     <nav className="border-b bg-background/95 backdrop-blur supports-[backdrop-filter]:bg-background/60">
@@ -57,6 +73,14 @@ export function Navigation() {
               )
             })}
           </div>
+
+          <button
+            onClick={logout}
+            className="ml-auto flex items-center space-x-2 text-sm font-medium text-muted-foreground transition-colors hover:text-primary"
+          >
+            <LogOut className="h-4 w-4" />
+            <span>Sign out</span>
+          </button>
         </div>
       </div>
     </nav>
@@ -9,9 +9,27 @@ export const api = axios.create({
   },
 })
 
-// Request interceptor for debugging
+const TOKEN_KEY = 'pc-inventory-token'
+
+export function getToken(): string | null {
+  return typeof window === 'undefined' ? null : localStorage.getItem(TOKEN_KEY)
+}
+
+export function setToken(token: string) {
+  localStorage.setItem(TOKEN_KEY, token)
+}
+
+export function clearToken() {
+  localStorage.removeItem(TOKEN_KEY)
+}
+
+// Request interceptor for debugging and attaching the session token
 api.interceptors.request.use((config) => {
   console.log(`Making ${config.method?.toUpperCase()} request to ${config.url}`)
+  const token = getToken()
+  if (token) {
+    config.headers.Authorization = `Bearer ${token}`
+  }
   return config
 })
 
@@ -20,6 +38,11 @@ api.interceptors.response.use(
   (response) => response,
   (error) => {
     console.error('API Error:', error.response?.data || error.message)
+    // Expired or missing session: send the user back to the login page
+    if (error.response?.status === 401 && typeof window !== 'undefined' && window.location.pathname !== '/login') {
+      clearToken()
+      window.location.href = '/login'
+    }
     return Promise.reject(error)
   }
 )
\ No newline at end of file
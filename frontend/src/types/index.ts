@@ -15,9 +15,23 @@ export interface Pc {
   profit?: number
   profit_percentage?: number
   notes?: string
-  status: 'building' | 'listed' | 'sold' | 'archived'
+  status: PcStatus
   created_at: string
   updated_at: string
+  deleted_at?: string
+  days_on_market?: number
+}
+
+export type PcStatus = 'building' | 'listed' | 'sold' | 'archived'
+
+export interface PcStatusChange {
+  id: string
+  pc_id: string
+  from_status?: PcStatus
+  to_status: PcStatus
+  changed_by?: string
+  reason?: string
+  changed_at: string
 }
 
 export interface Component {
@@ -27,19 +41,285 @@ export interface Component {
   component_name: string
   cost: number
   notes?: string
+  inventory_part_id?: string
+  // The inventory lot the part was taken from
+  lot_id?: string
+  specs: ComponentSpecs
+}
+
+export interface ComponentSpecs {
+  socket?: string
+  memory_type?: string
+  form_factor?: string
+  tdp_watts?: number
+  wattage?: number
+  length_mm?: number
+  max_gpu_length_mm?: number
+}
+
+export interface CompatibilityIssue {
+  severity: 'error' | 'warning'
+  code: string
+  message: string
+  component_ids: string[]
+}
+
+export interface CompatibilityReport {
+  pc_id: string
+  compatible: boolean
+  estimated_power_draw_watts?: number
+  psu_wattage?: number
+  issues: CompatibilityIssue[]
+}
+
+export interface ComparableBuild {
+  pc_id: string
+  pc_name: string
+  total_cost: number
+  actual_sale_price: number
+  profit_percentage: number
+  days_listed?: number
+  sale_date?: string
+  similarity: number
+  shared_components: string[]
+}
+
+export interface PriceSuggestion {
+  pc_id: string
+  total_cost: number
+  target_margin: number
+  suggested_price: number
+  comparable_margin?: number
+  expected_days_to_sell?: number
+  comparables: ComparableBuild[]
+}
+
+export interface ListingSettings {
+  id: string
+  stale_after_days: number
+  price_drop_percent?: number
+  price_drop_interval_days?: number
+  min_margin: number
+  created_at: string
+  updated_at: string
+}
+
+export interface PriceDrop {
+  due: string
+  price: number
+}
+
+export interface StaleListing extends Pc {
+  next_price_drop?: PriceDrop
+}
+
+export interface PcPriceChange {
+  id: string
+  pc_id: string
+  old_price?: number
+  new_price?: number
+  scheduled: boolean
+  changed_by?: string
+  reason?: string
+  changed_at: string
+}
+
+export interface PartPriceChange {
+  id: string
+  part_id: string
+  price_type: 'buy_in' | 'typical_sell'
+  old_price?: number
+  new_price?: number
+  changed_by?: string
+  reason?: string
+  changed_at: string
+}
+
+export type StockMovementType = 'receive' | 'consume' | 'sell' | 'return' | 'adjust' | 'write_off'
+
+export interface StockMovement {
+  id: string
+  part_id: string
+  movement_type: StockMovementType
+  // Signed: positive adds stock
+  quantity: number
+  balance_after: number
+  pc_id?: string
+  purchase_order_id?: string
+  lot_id?: string
+  // Cost per part moved
+  unit_cost?: number
+  reason?: string
+  reference?: string
+  created_by?: string
+  created_at: string
+}
+
+export interface CreateStockMovementRequest {
+  movement_type: Exclude<StockMovementType, 'consume'>
+  quantity: number
+  unit_cost?: number
+  reason?: string
+  reference?: string
+}
+
+export type ItemCondition = 'new' | 'like_new' | 'used' | 'refurbished' | 'faulty'
+
+export type TestStatus = 'untested' | 'passed' | 'failed'
+
+export interface SerializedItem {
+  id: string
+  part_id: string
+  serial_number: string
+  condition: ItemCondition
+  test_status: TestStatus
+  acquired_on?: string
+  acquired_from?: string
+  supplier_id?: string
+  // The component it was built in as; empty while on the shelf
+  component_id?: string
+  notes?: string
+  created_at: string
+  updated_at: string
+}
+
+export interface CreateSerializedItemRequest {
+  serial_number: string
+  condition?: ItemCondition
+  test_status?: TestStatus
+  acquired_on?: string
+  acquired_from?: string
+  supplier_id?: string
+  notes?: string
+}
+
+export type ItemLocation = 'in_stock' | 'in_build' | 'sold'
+
+export interface SerialLocation extends SerializedItem {
+  component_type: ComponentType
+  component_name: string
+  location: ItemLocation
+  pc_id?: string
+  pc_name?: string
+  pc_status?: PcStatus
+  sale_date?: string
+  platform?: string
+  buyer_id?: string
+  buyer_name?: string
+}
+
+export type CostingMethod = 'fifo' | 'weighted_average'
+
+export interface InventorySettings {
+  id: string
+  costing_method: CostingMethod
+  created_at: string
+  updated_at: string
+}
+
+export interface InventoryLot {
+  id: string
+  part_id: string
+  purchase_order_id?: string
+  unit_cost?: number
+  quantity_received: number
+  quantity_remaining: number
+  received_at: string
+}
+
+export interface Supplier {
+  id: string
+  name: string
+  website?: string
+  contact?: string
+  email?: string
+  phone?: string
+  notes?: string
+  created_at: string
+  updated_at: string
 }
 
-export type ComponentType = 
-  | 'Cpu'
-  | 'Gpu' 
-  | 'Motherboard'
-  | 'Ram'
-  | 'Storage1'
-  | 'Storage2'
-  | 'Psu'
-  | 'Case'
-  | 'CpuCooler'
-  | 'Additional'
+export interface CreateSupplierRequest {
+  name: string
+  website?: string
+  contact?: string
+  email?: string
+  phone?: string
+  notes?: string
+}
+
+export type PurchaseOrderStatus = 'ordered' | 'partially_received' | 'received' | 'cancelled'
+
+export interface PurchaseOrder {
+  id: string
+  supplier_id: string
+  order_reference?: string
+  order_date: string
+  expected_date?: string
+  status: PurchaseOrderStatus
+  shipping_cost: number
+  customs_cost: number
+  notes?: string
+  created_at: string
+  updated_at: string
+}
+
+export interface PurchaseOrderLine {
+  id: string
+  purchase_order_id: string
+  part_id: string
+  component_type: ComponentType
+  component_name: string
+  quantity_ordered: number
+  quantity_received: number
+  unit_cost: number
+  // Unit cost plus its share of shipping and customs
+  landed_unit_cost: number
+}
+
+export interface PurchaseOrderWithLines extends PurchaseOrder {
+  lines: PurchaseOrderLine[]
+  total_cost: number
+}
+
+export interface CreatePurchaseOrderRequest {
+  supplier_id: string
+  order_reference?: string
+  order_date?: string
+  expected_date?: string
+  shipping_cost?: number
+  customs_cost?: number
+  notes?: string
+  lines: { part_id: string; quantity: number; unit_cost: number }[]
+}
+
+export interface ReceivePurchaseOrderRequest {
+  lines: { line_id: string; quantity: number }[]
+}
+
+export interface StockDiscrepancy {
+  part_id: string
+  component_type: ComponentType
+  component_name: string
+  quantity_available: number
+  ledger_quantity: number
+  difference: number
+}
+
+// Slug of a component category, e.g. 'gpu' or 'case_fan'
+export type ComponentType = string
+
+export interface ComponentCategory {
+  id: string
+  slug: ComponentType
+  name: string
+  sort_order: number
+  max_per_pc?: number
+  reorder_point?: number
+  target_stock?: number
+  created_at: string
+  updated_at: string
+}
 
 export interface PcWithComponents extends Pc {
   components: Component[]
@@ -52,10 +332,18 @@ export interface InventoryPart {
   buy_in_price?: number
   typical_sell_price?: number
   quantity_available: number
+  reorder_point?: number
+  target_stock?: number
   notes?: string
   purchase_link?: string
   created_at: string
   updated_at: string
+  deleted_at?: string
+}
+
+export interface LowStockPart extends InventoryPart {
+  effective_reorder_point: number
+  effective_target_stock?: number
 }
 
 export interface CreateInventoryPartRequest {
@@ -64,6 +352,8 @@ export interface CreateInventoryPartRequest {
   buy_in_price?: number
   typical_sell_price?: number
   quantity_available?: number
+  reorder_point?: number
+  target_stock?: number
   notes?: string
   purchase_link?: string
 }
@@ -72,7 +362,10 @@ export interface UpdateInventoryPartRequest {
   component_name?: string
   buy_in_price?: number
   typical_sell_price?: number
+  price_change_reason?: string
   quantity_available?: number
+  reorder_point?: number
+  target_stock?: number
   notes?: string
   purchase_link?: string
 }
@@ -84,6 +377,7 @@ export interface Buyer {
   email?: string
   phone?: string
   created_at: string
+  deleted_at?: string
 }
 
 export interface CreateBuyerRequest {
@@ -93,6 +387,25 @@ export interface CreateBuyerRequest {
   phone?: string
 }
 
+export interface UpdateBuyerRequest {
+  name?: string
+  contact?: string
+  email?: string
+  phone?: string
+}
+
+export interface MergeBuyersResult {
+  buyer: Buyer
+  merged_ids: string[]
+  pcs_moved: number
+}
+
+export interface DuplicateBuyerGroup {
+  matched_on: 'name' | 'email' | 'phone'
+  value: string
+  buyers: Buyer[]
+}
+
 export interface CreatePcRequest {
   pc_name: string
   build_date?: string
@@ -104,8 +417,17 @@ export interface CreatePcRequest {
 export interface CreateComponentRequest {
   component_type: ComponentType
   component_name: string
-  cost: number
+  cost?: number
   notes?: string
+  inventory_part_id?: string
+}
+
+export interface UpdateComponentRequest {
+  component_type?: ComponentType
+  component_name?: string
+  cost?: number
+  notes?: string
+  inventory_part_id?: string
 }
 
 export interface MonthlySummary {
@@ -122,4 +444,148 @@ export interface ProfitAnalysis {
   avg_cost?: number
   total_usage: number
   avg_profit_contribution?: number
-}
\ No newline at end of file
+}
+
+export interface BuyerLifetimeValue {
+  buyer_id: string
+  name: string
+  purchases: number
+  total_spent: number
+  total_profit: number
+  first_purchase?: string
+  last_purchase?: string
+  average_days_between_purchases?: number
+  preferred_platform?: string
+}
+
+export interface TopBuyer extends BuyerLifetimeValue {
+  rank: number
+}
+
+export interface SaleDiscount {
+  pc_id: string
+  pc_name: string
+  sale_date?: string
+  original_price: number
+  final_asking_price?: number
+  actual_sale_price: number
+  discount: number
+  discount_percentage?: number
+  price_changes: number
+}
+
+export interface DiscountReport {
+  sales: SaleDiscount[]
+  total_original_price: number
+  total_sale_price: number
+  total_discount: number
+  discount_percentage?: number
+}
+
+export interface ReorderSuggestion {
+  part_id: string
+  component_type: ComponentType
+  component_name: string
+  quantity_available: number
+  reorder_point: number
+  target_stock?: number
+  consumed: number
+  on_order: number
+  daily_consumption: number
+  days_of_stock?: number
+  suggested_quantity: number
+  estimated_cost?: number
+}
+
+export interface ReorderReport {
+  days: number
+  cover_days: number
+  parts: ReorderSuggestion[]
+  total_estimated_cost: number
+}
+
+export type UserRole = 'owner' | 'builder' | 'viewer'
+
+export interface User {
+  id: string
+  username: string
+  display_name?: string
+  role: UserRole
+  created_at: string
+  updated_at: string
+}
+
+export interface LoginRequest {
+  username: string
+  password: string
+}
+
+export interface LoginResponse {
+  token: string
+  expires_at: string
+  user: User
+}
+
+export interface Page<T> {
+  items: T[]
+  total: number
+  page: number
+  per_page: number
+}
+
+export type AuditEntity = 'pc' | 'component' | 'part' | 'buyer'
+
+export type AuditAction = 'create' | 'update' | 'delete' | 'sell' | 'restore' | 'purge' | 'merge'
+
+export interface AuditEvent {
+  id: number
+  entity_type: AuditEntity
+  entity_id: string
+  action: AuditAction
+  actor_id?: string
+  actor: string
+  before?: Record<string, unknown>
+  after?: Record<string, unknown>
+  occurred_at: string
+}
+
+export interface FieldError {
+  field: string
+  code: string
+  message: string
+}
+
+export interface ApiError {
+  error: string
+  code: string
+  field?: string
+  fields?: FieldError[]
+}
+
+export interface Trash {
+  pcs: Pc[]
+  parts: InventoryPart[]
+  buyers: Buyer[]
+}
+
+export type RowOutcome = 'imported' | 'skipped' | 'failed'
+
+export interface ImportRowResult {
+  sheet: string
+  row: number
+  outcome: RowOutcome
+  name?: string
+  message?: string
+}
+
+export interface ImportReport {
+  committed: boolean
+  dry_run: boolean
+  imported: number
+  skipped: number
+  failed: number
+  buyers_created: number
+  rows: ImportRowResult[]
+}
+
+export type CsvEntity = 'pcs' | 'inventory' | 'buyers'
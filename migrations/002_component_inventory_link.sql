@@ -0,0 +1,12 @@
+-- Link PC components to the inventory part they were built from
+ALTER TABLE pc_components
+    ADD COLUMN inventory_part_id UUID REFERENCES parts_inventory(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_pc_components_inventory_part_id ON pc_components(inventory_part_id);
+
+-- Stock can never go negative
+UPDATE parts_inventory SET quantity_available = 0 WHERE quantity_available < 0 OR quantity_available IS NULL;
+
+ALTER TABLE parts_inventory
+    ALTER COLUMN quantity_available SET NOT NULL,
+    ADD CONSTRAINT parts_inventory_quantity_non_negative CHECK (quantity_available >= 0);
@@ -0,0 +1,20 @@
+-- Record every PC status change
+CREATE TABLE pc_status_history (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    pc_id UUID NOT NULL REFERENCES pcs(id) ON DELETE CASCADE,
+    from_status pc_status,
+    to_status pc_status NOT NULL,
+    changed_by VARCHAR(255),
+    reason TEXT,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_pc_status_history_pc_id ON pc_status_history(pc_id, changed_at);
+
+UPDATE pcs SET status = 'building' WHERE status IS NULL;
+ALTER TABLE pcs ALTER COLUMN status SET NOT NULL;
+
+-- Seed history with the current status of existing PCs
+INSERT INTO pc_status_history (pc_id, from_status, to_status, reason, changed_at)
+SELECT id, NULL, status, 'Initial status', COALESCE(created_at, NOW())
+FROM pcs;
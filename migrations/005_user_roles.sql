@@ -0,0 +1,7 @@
+-- Roles decide what each account may change
+CREATE TYPE user_role AS ENUM ('owner', 'builder', 'viewer');
+
+ALTER TABLE users ADD COLUMN role user_role NOT NULL DEFAULT 'viewer';
+
+-- Accounts created before roles existed had full access
+UPDATE users SET role = 'owner';
@@ -0,0 +1,8 @@
+-- Deleting moves PCs, parts and buyers to the trash; only an explicit purge removes the row
+ALTER TABLE pcs ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE parts_inventory ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE buyers ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_pcs_deleted_at ON pcs(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_parts_inventory_deleted_at ON parts_inventory(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_buyers_deleted_at ON buyers(deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,81 @@
+-- Component categories move from the fixed component_type enum to a table that both
+-- pc_components and the free-text parts_inventory.component_type reference by slug
+CREATE TABLE component_categories (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    slug VARCHAR(50) NOT NULL UNIQUE CHECK (slug ~ '^[a-z0-9]+(_[a-z0-9]+)*$'),
+    name VARCHAR(100) NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    -- How many of this category one PC takes, e.g. one CPU; NULL for no limit
+    max_per_pc INTEGER CHECK (max_per_pc > 0),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+INSERT INTO component_categories (slug, name, sort_order, max_per_pc) VALUES
+    ('cpu', 'CPU', 10, 1),
+    ('gpu', 'GPU', 20, NULL),
+    ('motherboard', 'Motherboard', 30, 1),
+    ('ram', 'RAM', 40, NULL),
+    ('storage', 'Storage', 50, NULL),
+    ('psu', 'PSU', 60, 1),
+    ('case', 'Case', 70, 1),
+    ('cpu_cooler', 'CPU Cooler', 80, 1),
+    ('case_fan', 'Case Fan', 90, NULL),
+    ('wifi_card', 'Wi-Fi Card', 100, NULL),
+    ('cable', 'Cables', 110, NULL),
+    ('os_license', 'OS License', 120, 1),
+    ('thermal_paste', 'Thermal Paste', 130, NULL),
+    ('other', 'Other', 1000, NULL);
+
+CREATE TRIGGER trigger_update_component_categories_updated_at
+    BEFORE UPDATE ON component_categories
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at();
+
+-- Turns free text such as 'GPU', 'CpuCooler' or 'Storage 1' into a slug, the same
+-- way the app normalises input
+CREATE FUNCTION component_category_slug(input TEXT) RETURNS TEXT AS $$
+DECLARE
+    slug TEXT;
+BEGIN
+    slug := TRIM(BOTH '_' FROM REGEXP_REPLACE(
+        LOWER(REGEXP_REPLACE(input, '([a-z0-9])([A-Z])', '\1_\2', 'g')),
+        '[^a-z0-9]+', '_', 'g'
+    ));
+
+    RETURN CASE
+        WHEN slug = '' THEN 'other'
+        WHEN slug IN ('storage1', 'storage2', 'storage_1', 'storage_2') THEN 'storage'
+        WHEN slug IN ('additional', 'additional_parts') THEN 'other'
+        WHEN slug IN ('cpucooler', 'cooler') THEN 'cpu_cooler'
+        WHEN slug = 'mb' THEN 'motherboard'
+        WHEN slug IN ('fan', 'fans', 'case_fans') THEN 'case_fan'
+        WHEN slug IN ('wifi', 'wi_fi', 'wi_fi_card') THEN 'wifi_card'
+        WHEN slug = 'cables' THEN 'cable'
+        WHEN slug = 'os' THEN 'os_license'
+        ELSE slug
+    END;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+ALTER TABLE pc_components
+    ALTER COLUMN component_type TYPE VARCHAR(50) USING component_category_slug(component_type::text);
+
+UPDATE parts_inventory SET component_type = component_category_slug(component_type);
+
+-- Inventory may hold types nobody planned for; they become categories of their own
+INSERT INTO component_categories (slug, name, sort_order)
+SELECT DISTINCT component_type, INITCAP(REPLACE(component_type, '_', ' ')), 500
+FROM parts_inventory
+WHERE component_type NOT IN (SELECT slug FROM component_categories);
+
+DROP FUNCTION component_category_slug(TEXT);
+
+ALTER TABLE pc_components
+    ADD CONSTRAINT pc_components_component_type_fkey
+    FOREIGN KEY (component_type) REFERENCES component_categories(slug);
+ALTER TABLE parts_inventory
+    ADD CONSTRAINT parts_inventory_component_type_fkey
+    FOREIGN KEY (component_type) REFERENCES component_categories(slug);
+
+DROP TYPE component_type;
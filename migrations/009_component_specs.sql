@@ -0,0 +1,3 @@
+-- Spec attributes the compatibility check reads: socket, memory type, form factor,
+-- TDP, PSU wattage and GPU/case lengths. Only the keys a component has are stored.
+ALTER TABLE pc_components ADD COLUMN specs JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,36 @@
+-- When a listing counts as stale and how its price comes down; a single row
+CREATE TABLE listing_settings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    singleton BOOLEAN NOT NULL DEFAULT TRUE UNIQUE CHECK (singleton),
+    stale_after_days INTEGER NOT NULL DEFAULT 30 CHECK (stale_after_days >= 0),
+    -- Price-drop schedule: once stale, cut the price by this percentage every
+    -- interval, never below total cost plus min_margin percent. Off while NULL.
+    price_drop_percent DECIMAL(5,2) CHECK (price_drop_percent > 0 AND price_drop_percent < 100),
+    price_drop_interval_days INTEGER CHECK (price_drop_interval_days > 0),
+    min_margin DECIMAL(5,2) NOT NULL DEFAULT 0 CHECK (min_margin > -100),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK ((price_drop_percent IS NULL) = (price_drop_interval_days IS NULL))
+);
+
+INSERT INTO listing_settings DEFAULT VALUES;
+
+CREATE TRIGGER trigger_update_listing_settings_updated_at
+    BEFORE UPDATE ON listing_settings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at();
+
+-- Every change to a PC's intended price
+CREATE TABLE pc_price_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    pc_id UUID NOT NULL REFERENCES pcs(id) ON DELETE CASCADE,
+    old_price DECIMAL(10,2),
+    new_price DECIMAL(10,2),
+    -- Made by the price-drop schedule rather than by hand
+    scheduled BOOLEAN NOT NULL DEFAULT FALSE,
+    changed_by VARCHAR(255),
+    reason TEXT,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_pc_price_changes_pc_id ON pc_price_changes(pc_id, changed_at);
@@ -0,0 +1,33 @@
+-- Every change to a part's buy-in or typical sell price
+CREATE TYPE part_price_type AS ENUM ('buy_in', 'typical_sell');
+
+CREATE TABLE part_price_changes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    part_id UUID NOT NULL REFERENCES parts_inventory(id) ON DELETE CASCADE,
+    price_type part_price_type NOT NULL,
+    old_price DECIMAL(10,2),
+    new_price DECIMAL(10,2),
+    changed_by VARCHAR(255),
+    reason TEXT,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_part_price_changes_part_id ON part_price_changes(part_id, changed_at);
+
+-- Seed history with the current prices, unless the price-drop schedule already
+-- recorded a PC's
+INSERT INTO pc_price_changes (pc_id, old_price, new_price, reason, changed_at)
+SELECT id, NULL, intended_price, 'Initial price', COALESCE(created_at, NOW())
+FROM pcs
+WHERE intended_price IS NOT NULL
+    AND id NOT IN (SELECT pc_id FROM pc_price_changes);
+
+INSERT INTO part_price_changes (part_id, price_type, old_price, new_price, reason, changed_at)
+SELECT id, 'buy_in', NULL, buy_in_price, 'Initial price', COALESCE(created_at, NOW())
+FROM parts_inventory
+WHERE buy_in_price IS NOT NULL;
+
+INSERT INTO part_price_changes (part_id, price_type, old_price, new_price, reason, changed_at)
+SELECT id, 'typical_sell', NULL, typical_sell_price, 'Initial price', COALESCE(created_at, NOW())
+FROM parts_inventory
+WHERE typical_sell_price IS NOT NULL;
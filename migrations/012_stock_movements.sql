@@ -0,0 +1,28 @@
+-- Every change to a part's stock; quantity_available is the running balance
+CREATE TYPE stock_movement_type AS ENUM ('receive', 'consume', 'sell', 'return', 'adjust', 'write_off');
+
+CREATE TABLE stock_movements (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    part_id UUID NOT NULL REFERENCES parts_inventory(id) ON DELETE CASCADE,
+    movement_type stock_movement_type NOT NULL,
+    -- Signed: positive adds stock
+    quantity INTEGER NOT NULL CHECK (quantity <> 0),
+    -- quantity_available right after the movement
+    balance_after INTEGER NOT NULL,
+    -- The PC a part was built into or taken back out of
+    pc_id UUID REFERENCES pcs(id) ON DELETE SET NULL,
+    reason TEXT,
+    -- Order number, listing, RMA or the like
+    reference VARCHAR(255),
+    created_by VARCHAR(255),
+    -- Clock time rather than transaction time, so movements made together stay in order
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX idx_stock_movements_part_id ON stock_movements(part_id, created_at);
+
+-- Open the ledger with the stock on hand
+INSERT INTO stock_movements (part_id, movement_type, quantity, balance_after, reason, created_at)
+SELECT id, 'adjust', quantity_available, quantity_available, 'Opening balance', COALESCE(created_at, NOW())
+FROM parts_inventory
+WHERE quantity_available > 0;
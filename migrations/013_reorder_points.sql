@@ -0,0 +1,9 @@
+-- When a part counts as low on stock and how much to order; a part's own
+-- levels override its category's
+ALTER TABLE component_categories
+    ADD COLUMN reorder_point INTEGER CHECK (reorder_point >= 0),
+    ADD COLUMN target_stock INTEGER CHECK (target_stock >= 0);
+
+ALTER TABLE parts_inventory
+    ADD COLUMN reorder_point INTEGER CHECK (reorder_point >= 0),
+    ADD COLUMN target_stock INTEGER CHECK (target_stock >= 0);
@@ -0,0 +1,62 @@
+-- Where parts are bought: web shops, retailers, second-hand sellers
+CREATE TABLE suppliers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL UNIQUE,
+    website TEXT,
+    contact VARCHAR(255),
+    email VARCHAR(255),
+    phone VARCHAR(20),
+    notes TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TRIGGER trigger_update_suppliers_updated_at
+    BEFORE UPDATE ON suppliers
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at();
+
+CREATE TYPE purchase_order_status AS ENUM ('ordered', 'partially_received', 'received', 'cancelled');
+
+CREATE TABLE purchase_orders (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    supplier_id UUID NOT NULL REFERENCES suppliers(id),
+    -- The supplier's order number
+    order_reference VARCHAR(100),
+    order_date DATE NOT NULL DEFAULT CURRENT_DATE,
+    expected_date DATE,
+    status purchase_order_status NOT NULL DEFAULT 'ordered',
+    -- Split across the lines by value into their landed cost
+    shipping_cost DECIMAL(10,2) NOT NULL DEFAULT 0 CHECK (shipping_cost >= 0),
+    customs_cost DECIMAL(10,2) NOT NULL DEFAULT 0 CHECK (customs_cost >= 0),
+    notes TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_purchase_orders_supplier_id ON purchase_orders(supplier_id);
+CREATE INDEX idx_purchase_orders_status ON purchase_orders(status);
+
+CREATE TRIGGER trigger_update_purchase_orders_updated_at
+    BEFORE UPDATE ON purchase_orders
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at();
+
+CREATE TABLE purchase_order_lines (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    purchase_order_id UUID NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+    part_id UUID NOT NULL REFERENCES parts_inventory(id),
+    quantity_ordered INTEGER NOT NULL CHECK (quantity_ordered > 0),
+    quantity_received INTEGER NOT NULL DEFAULT 0
+        CHECK (quantity_received >= 0 AND quantity_received <= quantity_ordered),
+    unit_cost DECIMAL(10,2) NOT NULL CHECK (unit_cost >= 0),
+    -- Unit cost plus the line's share of shipping and customs
+    landed_unit_cost DECIMAL(10,2) NOT NULL CHECK (landed_unit_cost >= 0)
+);
+
+CREATE INDEX idx_purchase_order_lines_order_id ON purchase_order_lines(purchase_order_id);
+CREATE INDEX idx_purchase_order_lines_part_id ON purchase_order_lines(part_id);
+
+-- Stock received against a purchase order
+ALTER TABLE stock_movements
+    ADD COLUMN purchase_order_id UUID REFERENCES purchase_orders(id) ON DELETE SET NULL;
@@ -0,0 +1,50 @@
+-- How parts taken off the shelf are costed: at their oldest lot's cost, or at
+-- the average cost of everything on the shelf
+CREATE TYPE costing_method AS ENUM ('fifo', 'weighted_average');
+
+-- Inventory-wide settings; a single row
+CREATE TABLE inventory_settings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    singleton BOOLEAN NOT NULL DEFAULT TRUE UNIQUE CHECK (singleton),
+    costing_method costing_method NOT NULL DEFAULT 'fifo',
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+INSERT INTO inventory_settings DEFAULT VALUES;
+
+CREATE TRIGGER trigger_update_inventory_settings_updated_at
+    BEFORE UPDATE ON inventory_settings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at();
+
+-- Parts put on the shelf together at one unit cost; a part's lots add up to
+-- its quantity_available
+CREATE TABLE inventory_lots (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    part_id UUID NOT NULL REFERENCES parts_inventory(id) ON DELETE CASCADE,
+    purchase_order_id UUID REFERENCES purchase_orders(id) ON DELETE SET NULL,
+    -- NULL while the buy-in price isn't known
+    unit_cost DECIMAL(10,2) CHECK (unit_cost >= 0),
+    quantity_received INTEGER NOT NULL CHECK (quantity_received > 0),
+    quantity_remaining INTEGER NOT NULL CHECK (quantity_remaining >= 0 AND quantity_remaining <= quantity_received),
+    received_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT clock_timestamp()
+);
+
+CREATE INDEX idx_inventory_lots_part_id ON inventory_lots(part_id, received_at);
+
+-- The lot a movement went into or came out of, when it was a single one, and
+-- the cost per part moved
+ALTER TABLE stock_movements
+    ADD COLUMN lot_id UUID REFERENCES inventory_lots(id) ON DELETE SET NULL,
+    ADD COLUMN unit_cost DECIMAL(10,2);
+
+-- The lot a component was taken from, which it goes back to if removed
+ALTER TABLE pc_components
+    ADD COLUMN lot_id UUID REFERENCES inventory_lots(id) ON DELETE SET NULL;
+
+-- The stock on hand becomes one lot per part at its buy-in price
+INSERT INTO inventory_lots (part_id, unit_cost, quantity_received, quantity_remaining, received_at)
+SELECT id, buy_in_price, quantity_available, quantity_available, COALESCE(created_at, NOW())
+FROM parts_inventory
+WHERE quantity_available > 0;
@@ -0,0 +1,30 @@
+CREATE TYPE item_condition AS ENUM ('new', 'like_new', 'used', 'refurbished', 'faulty');
+CREATE TYPE item_test_status AS ENUM ('untested', 'passed', 'failed');
+
+-- Individual physical units of a part, tracked by serial number for warranty
+-- and returns
+CREATE TABLE serialized_items (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- Kept while the unit is on record, so a part with serials can't be purged
+    part_id UUID NOT NULL REFERENCES parts_inventory(id),
+    serial_number VARCHAR(100) NOT NULL,
+    condition item_condition NOT NULL DEFAULT 'new',
+    test_status item_test_status NOT NULL DEFAULT 'untested',
+    acquired_on DATE,
+    -- Who it came from: a seller, a listing, a trade-in
+    acquired_from VARCHAR(255),
+    supplier_id UUID REFERENCES suppliers(id) ON DELETE SET NULL,
+    -- The build it went into; back on the shelf when the component goes
+    component_id UUID UNIQUE REFERENCES pc_components(id) ON DELETE SET NULL,
+    notes TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT serialized_items_serial_number_key UNIQUE (part_id, serial_number)
+);
+
+CREATE INDEX idx_serialized_items_serial_number ON serialized_items(LOWER(serial_number));
+
+CREATE TRIGGER trigger_update_serialized_items_updated_at
+    BEFORE UPDATE ON serialized_items
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at();
@@ -0,0 +1,5 @@
+-- Days a listed PC has been on the market so far; days_listed is only filled
+-- in once it sells. Empty for PCs that aren't listed.
+CREATE FUNCTION days_on_market(pc pcs) RETURNS INTEGER AS $$
+    SELECT CASE WHEN pc.status = 'listed' THEN CURRENT_DATE - pc.list_date END
+$$ LANGUAGE SQL STABLE;
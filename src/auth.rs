@@ -0,0 +1,141 @@
+use std::future::{ready, Ready};
+
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    web, FromRequest, HttpMessage, HttpRequest,
+};
+use anyhow::Context;
+use argon2::{
+    password_hash::{rand_core::{OsRng, RngCore}, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::db;
+use crate::error::ApiError;
+use crate::models::{Actor, Permission, User};
+
+/// How long a bearer token stays valid after login
+pub const SESSION_LIFETIME_DAYS: i64 = 7;
+
+/// The signed-in user, attached to the request by [`require_auth`].
+/// Handlers take it as an extractor to know who is acting.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user: User,
+    pub token_hash: String,
+}
+
+impl AuthUser {
+    /// The user as recorded against the changes they make
+    pub fn actor(&self) -> Actor {
+        Actor {
+            user_id: Some(self.user.id),
+            name: self.user.username.clone(),
+        }
+    }
+
+    pub fn can(&self, permission: Permission) -> bool {
+        self.user.role.can(permission)
+    }
+
+    /// Rejects the request with `403 forbidden` unless the user's role allows `permission`
+    pub fn require(&self, permission: Permission) -> Result<(), ApiError> {
+        if self.can(permission) {
+            Ok(())
+        } else {
+            Err(ApiError::forbidden(format!(
+                "The {} role is not allowed to do this",
+                self.user.role.as_str()
+            )))
+        }
+    }
+}
+
+impl FromRequest for AuthUser {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<AuthUser>()
+                .cloned()
+                .ok_or_else(|| ApiError::unauthorized("Authentication required")),
+        )
+    }
+}
+
+/// Middleware for everything under `/api` except login: resolves the bearer
+/// token to a user or rejects the request with `401 unauthorized`.
+pub async fn require_auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    match authenticate(&req).await {
+        Ok(auth_user) => {
+            req.extensions_mut().insert(auth_user);
+            Ok(next.call(req).await?.map_into_boxed_body())
+        }
+        Err(error) => Ok(req.error_response(error)),
+    }
+}
+
+async fn authenticate(req: &ServiceRequest) -> Result<AuthUser, ApiError> {
+    let token = bearer_token(req.request())
+        .ok_or_else(|| ApiError::unauthorized("Missing bearer token"))?;
+    let token_hash = hash_token(&token);
+
+    let pool = req
+        .app_data::<web::Data<PgPool>>()
+        .context("Database pool is not configured")?;
+
+    let user = db::get_session_user(pool, &token_hash)
+        .await
+        .context("Failed to check session")?
+        .ok_or_else(|| ApiError::unauthorized("Session is invalid or has expired"))?;
+
+    Ok(AuthUser { user, token_hash })
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+
+    if scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty() {
+        Some(token.trim().to_string())
+    } else {
+        None
+    }
+}
+
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|error| anyhow::anyhow!("Failed to hash password: {}", error))?;
+
+    Ok(hash.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+        .unwrap_or(false)
+}
+
+/// A random 256-bit bearer token, hex encoded
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Tokens are stored hashed so a leaked sessions table can't be replayed
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
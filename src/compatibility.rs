@@ -0,0 +1,212 @@
+//! Checks that a build's parts fit together, from the specs on its components
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::models::{CompatibilityIssue, CompatibilityReport, Component, ComponentType, IssueSeverity};
+
+/// Motherboard, memory, drives and fans, which rarely list a TDP
+const BASE_SYSTEM_WATTS: i32 = 50;
+
+/// How far above the estimated draw a PSU should be rated, in percent
+const PSU_HEADROOM_PERCENT: i32 = 25;
+
+/// Board sizes from smallest to largest, by normalised name
+const FORM_FACTORS: [(&str, u8); 8] = [
+    ("miniitx", 1),
+    ("itx", 1),
+    ("microatx", 2),
+    ("matx", 2),
+    ("uatx", 2),
+    ("atx", 3),
+    ("eatx", 4),
+    ("extendedatx", 4),
+];
+
+/// Checks a PC's components against each other and against `limits`, the
+/// `max_per_pc` of each category that has one
+pub fn check_build(pc_id: Uuid, components: &[Component], limits: &HashMap<ComponentType, i32>) -> CompatibilityReport {
+    let of_type = |slug: &str| -> Vec<&Component> {
+        components
+            .iter()
+            .filter(|component| component.component_type.as_str() == slug)
+            .collect()
+    };
+    let cpus = of_type("cpu");
+    let boards = of_type("motherboard");
+    let memory = of_type("ram");
+    let gpus = of_type("gpu");
+    let psus = of_type("psu");
+    let cases = of_type("case");
+
+    let mut issues = Vec::new();
+
+    let mut counted: Vec<&ComponentType> = Vec::new();
+    for component in components {
+        let component_type = &component.component_type;
+        if counted.contains(&component_type) {
+            continue;
+        }
+        counted.push(component_type);
+
+        let Some(&max) = limits.get(component_type) else {
+            continue;
+        };
+        let same_type: Vec<&Component> = components
+            .iter()
+            .filter(|other| &other.component_type == component_type)
+            .collect();
+        if same_type.len() > max as usize {
+            issues.push(error(
+                "too_many_components",
+                format!("{} {} components, but a PC takes {}", same_type.len(), component_type, max),
+                &same_type,
+            ));
+        }
+    }
+
+    for cpu in &cpus {
+        for board in &boards {
+            if let Some((cpu_socket, board_socket)) = mismatch(&cpu.specs.socket, &board.specs.socket) {
+                issues.push(error(
+                    "socket_mismatch",
+                    format!(
+                        "CPU {} ({}) doesn't fit motherboard {} ({})",
+                        cpu.component_name, cpu_socket, board.component_name, board_socket
+                    ),
+                    &[cpu, board],
+                ));
+            }
+        }
+    }
+
+    for stick in &memory {
+        for board in &boards {
+            if let Some((ram_type, board_type)) = mismatch(&stick.specs.memory_type, &board.specs.memory_type) {
+                issues.push(error(
+                    "memory_type_mismatch",
+                    format!(
+                        "RAM {} ({}) doesn't fit motherboard {} ({})",
+                        stick.component_name, ram_type, board.component_name, board_type
+                    ),
+                    &[stick, board],
+                ));
+            }
+        }
+    }
+
+    for board in &boards {
+        for case in &cases {
+            let (Some(board_size), Some(case_size)) = (&board.specs.form_factor, &case.specs.form_factor) else {
+                continue;
+            };
+            if let (Some(board_rank), Some(case_rank)) = (form_factor_rank(board_size), form_factor_rank(case_size)) {
+                if board_rank > case_rank {
+                    issues.push(error(
+                        "form_factor_mismatch",
+                        format!(
+                            "Motherboard {} ({}) is too large for case {} ({})",
+                            board.component_name, board_size, case.component_name, case_size
+                        ),
+                        &[board, case],
+                    ));
+                }
+            }
+        }
+    }
+
+    for gpu in &gpus {
+        for case in &cases {
+            if let (Some(length), Some(max_length)) = (gpu.specs.length_mm, case.specs.max_gpu_length_mm) {
+                if length > max_length {
+                    issues.push(error(
+                        "gpu_too_long",
+                        format!(
+                            "GPU {} is {} mm long, but case {} fits {} mm",
+                            gpu.component_name, length, case.component_name, max_length
+                        ),
+                        &[gpu, case],
+                    ));
+                }
+            }
+        }
+    }
+
+    let powered: Vec<&Component> = components
+        .iter()
+        .filter(|component| component.specs.tdp_watts.is_some())
+        .collect();
+    let estimated_power_draw_watts = (!powered.is_empty()).then(|| {
+        BASE_SYSTEM_WATTS + powered.iter().filter_map(|component| component.specs.tdp_watts).sum::<i32>()
+    });
+    let psu = psus.iter().find(|psu| psu.specs.wattage.is_some());
+    let psu_wattage = psu.and_then(|psu| psu.specs.wattage);
+
+    if let (Some(psu), Some(wattage), Some(draw)) = (psu, psu_wattage, estimated_power_draw_watts) {
+        let recommended = draw + draw * PSU_HEADROOM_PERCENT / 100;
+        let mut involved = vec![*psu];
+        involved.extend(&powered);
+        if wattage < draw {
+            issues.push(error(
+                "psu_insufficient",
+                format!("PSU {} is rated {} W, but the build draws about {} W", psu.component_name, wattage, draw),
+                &involved,
+            ));
+        } else if wattage < recommended {
+            issues.push(CompatibilityIssue {
+                severity: IssueSeverity::Warning,
+                code: "psu_headroom".to_string(),
+                message: format!(
+                    "PSU {} is rated {} W; about {} W is recommended for a {} W draw",
+                    psu.component_name, wattage, recommended, draw
+                ),
+                component_ids: ids(&involved),
+            });
+        }
+    }
+
+    CompatibilityReport {
+        pc_id,
+        compatible: !issues.iter().any(|issue| issue.severity == IssueSeverity::Error),
+        estimated_power_draw_watts,
+        psu_wattage,
+        issues,
+    }
+}
+
+fn error(code: &str, message: String, components: &[&Component]) -> CompatibilityIssue {
+    CompatibilityIssue {
+        severity: IssueSeverity::Error,
+        code: code.to_string(),
+        message,
+        component_ids: ids(components),
+    }
+}
+
+fn ids(components: &[&Component]) -> Vec<Uuid> {
+    components.iter().map(|component| component.id).collect()
+}
+
+/// Both values when they're given and differ, ignoring case, spaces and dashes,
+/// so "LGA 1200" matches "lga1200"
+fn mismatch<'a>(left: &'a Option<String>, right: &'a Option<String>) -> Option<(&'a str, &'a str)> {
+    let (left, right) = (left.as_deref()?, right.as_deref()?);
+    (normalise(left) != normalise(right)).then_some((left, right))
+}
+
+fn form_factor_rank(form_factor: &str) -> Option<u8> {
+    let form_factor = normalise(form_factor);
+    FORM_FACTORS
+        .iter()
+        .find(|(name, _)| *name == form_factor)
+        .map(|(_, rank)| *rank)
+}
+
+fn normalise(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
@@ -0,0 +1,67 @@
+//! Takes parts off the shelf from their lots, oldest first, and costs them
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::models::CostingMethod;
+
+/// A lot with stock left, as far as taking parts from it goes
+pub struct ShelfLot {
+    pub id: Uuid,
+    pub unit_cost: Option<Decimal>,
+    pub quantity_remaining: i32,
+}
+
+/// Which lots `quantity` parts come from and what each part costs
+pub struct Taken {
+    /// Lot and how many parts were taken from it, oldest first
+    pub lots: Vec<(Uuid, i32)>,
+    /// `None` when none of the lots it goes by has a cost
+    pub unit_cost: Option<Decimal>,
+}
+
+/// Takes `quantity` parts from `lots`, given oldest first. FIFO costs them at
+/// the lots they came from, weighted average at the average of every lot on
+/// the shelf. Lots without a cost are left out of the average. Takes what
+/// there is if the lots hold fewer parts.
+pub fn take_from_lots(method: CostingMethod, lots: &[ShelfLot], quantity: i32) -> Taken {
+    let mut left = quantity;
+    let mut taken = Vec::new();
+    for lot in lots {
+        if left <= 0 {
+            break;
+        }
+        let count = lot.quantity_remaining.min(left);
+        if count > 0 {
+            taken.push((lot.id, count));
+            left -= count;
+        }
+    }
+
+    let costed: Vec<(i32, Decimal)> = match method {
+        CostingMethod::Fifo => taken
+            .iter()
+            .filter_map(|(id, count)| {
+                let lot = lots.iter().find(|lot| lot.id == *id)?;
+                lot.unit_cost.map(|cost| (*count, cost))
+            })
+            .collect(),
+        CostingMethod::WeightedAverage => lots
+            .iter()
+            .filter_map(|lot| lot.unit_cost.map(|cost| (lot.quantity_remaining, cost)))
+            .collect(),
+    };
+
+    Taken { lots: taken, unit_cost: average_cost(&costed) }
+}
+
+/// Average unit cost of `(quantity, unit_cost)` pairs, to the cent
+fn average_cost(lots: &[(i32, Decimal)]) -> Option<Decimal> {
+    let quantity: i32 = lots.iter().map(|(quantity, _)| quantity).sum();
+    if quantity <= 0 {
+        return None;
+    }
+    let value: Decimal = lots.iter().map(|(quantity, cost)| Decimal::from(*quantity) * cost).sum();
+
+    Some((value / Decimal::from(quantity)).round_dp(2))
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::{Actor, AuditAction, AuditEntity, AuditEvent, AuditListQuery, Page, SortOrder, page_bounds};
+use super::filters::push_order_and_page;
+
+/// Columns that change on every write and would only add noise to a diff
+const IGNORED_FIELDS: [&str; 1] = ["updated_at"];
+
+pub async fn list_audit_events(pool: &PgPool, query: &AuditListQuery) -> Result<Page<AuditEvent>> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM audit_events WHERE TRUE");
+    push_audit_filters(&mut count, query);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+    let mut select = QueryBuilder::new(
+        "SELECT id, entity_type, entity_id, action, actor_id, actor, before, after, occurred_at \
+         FROM audit_events WHERE TRUE"
+    );
+    push_audit_filters(&mut select, query);
+    push_order_and_page(
+        &mut select,
+        &["occurred_at"],
+        query.order.unwrap_or(SortOrder::Desc),
+        page,
+        per_page,
+    );
+    let items = select.build_query_as::<AuditEvent>().fetch_all(pool).await?;
+
+    Ok(Page { items, total, page, per_page })
+}
+
+fn push_audit_filters(builder: &mut QueryBuilder<'_, Postgres>, query: &AuditListQuery) {
+    if let Some(entity_type) = query.entity_type {
+        builder.push(" AND entity_type = ").push_bind(entity_type.as_str());
+    }
+    if let Some(entity_id) = query.entity_id {
+        builder.push(" AND entity_id = ").push_bind(entity_id);
+    }
+    if let Some(action) = query.action {
+        builder.push(" AND action = ").push_bind(action.as_str());
+    }
+    if let Some(actor) = &query.actor {
+        builder.push(" AND LOWER(actor) = LOWER(").push_bind(actor.clone()).push(")");
+    }
+    if let Some(from) = query.from {
+        builder.push(" AND occurred_at >= ").push_bind(from).push("::date");
+    }
+    if let Some(to) = query.to {
+        builder.push(" AND occurred_at < ").push_bind(to).push("::date + 1");
+    }
+}
+
+/// Appends an event inside the caller's transaction so it commits or rolls back with the change.
+/// Updates that end up changing nothing are not recorded.
+pub(crate) async fn record_event<T: Serialize>(
+    conn: &mut PgConnection,
+    actor: &Actor,
+    entity: AuditEntity,
+    entity_id: Uuid,
+    action: AuditAction,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<()> {
+    let mut before = before.map(serde_json::to_value).transpose()?;
+    let mut after = after.map(serde_json::to_value).transpose()?;
+
+    if let (Some(old), Some(new)) = (&before, &after) {
+        let (old, new) = changed_fields(old, new);
+        if old.is_empty() && new.is_empty() {
+            return Ok(());
+        }
+        before = Some(Value::Object(old));
+        after = Some(Value::Object(new));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_events (entity_type, entity_id, action, actor_id, actor, before, after)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        entity.as_str(),
+        entity_id,
+        action.as_str(),
+        actor.user_id,
+        actor.name,
+        before,
+        after
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Keeps only the top-level fields whose values differ between the two snapshots
+fn changed_fields(before: &Value, after: &Value) -> (Map<String, Value>, Map<String, Value>) {
+    let (Value::Object(before), Value::Object(after)) = (before, after) else {
+        return (Map::new(), Map::new());
+    };
+
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut old = Map::new();
+    let mut new = Map::new();
+    for key in keys {
+        if IGNORED_FIELDS.contains(&key.as_str()) {
+            continue;
+        }
+        let old_value = before.get(key).unwrap_or(&Value::Null);
+        let new_value = after.get(key).unwrap_or(&Value::Null);
+        if old_value != new_value {
+            old.insert(key.clone(), old_value.clone());
+            new.insert(key.clone(), new_value.clone());
+        }
+    }
+
+    (old, new)
+}
@@ -1,15 +1,19 @@
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 use anyhow::Result;
 
-use crate::models::{Buyer, CreateBuyerRequest, Pc, PcStatus};
+use crate::models::{Buyer, CreateBuyerRequest, UpdateBuyerRequest, MergeBuyersResult, DuplicateBuyerGroup, DuplicateMatch, Pc, PcStatus, Page, BuyerListQuery, SortOrder, page_bounds, Actor, AuditAction, AuditEntity};
+use crate::validation::ValidationErrors;
+use super::audit_queries::record_event;
+use super::filters::{contains_pattern, push_order_and_page};
 
 pub async fn get_all_buyers(pool: &PgPool) -> Result<Vec<Buyer>> {
     let buyers = sqlx::query_as!(
         Buyer,
         r#"
-        SELECT id, name, contact, email, phone, created_at as "created_at!"
+        SELECT id, name, contact, email, phone, deleted_at, created_at as "created_at!"
         FROM buyers 
+        WHERE deleted_at IS NULL
         ORDER BY name
         "#
     )
@@ -19,13 +23,65 @@ pub async fn get_all_buyers(pool: &PgPool) -> Result<Vec<Buyer>> {
     Ok(buyers)
 }
 
-pub async fn create_buyer(pool: &PgPool, request: CreateBuyerRequest) -> Result<Buyer> {
+/// Without `search_contacts`, `q` only matches names so hidden contact details can't be probed
+pub async fn list_buyers(pool: &PgPool, query: &BuyerListQuery, search_contacts: bool) -> Result<Page<Buyer>> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+    
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM buyers WHERE deleted_at IS NULL");
+    push_buyer_filters(&mut count, query, search_contacts);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+    
+    let mut select = QueryBuilder::new(
+        "SELECT id, name, contact, email, phone, deleted_at, created_at FROM buyers WHERE deleted_at IS NULL"
+    );
+    push_buyer_filters(&mut select, query, search_contacts);
+    push_order_and_page(
+        &mut select,
+        &[query.sort.unwrap_or_default().column()],
+        query.order.unwrap_or(SortOrder::Asc),
+        page,
+        per_page,
+    );
+    let items = select.build_query_as::<Buyer>().fetch_all(pool).await?;
+    
+    Ok(Page { items, total, page, per_page })
+}
+
+fn push_buyer_filters(builder: &mut QueryBuilder<'_, Postgres>, query: &BuyerListQuery, search_contacts: bool) {
+    let Some(q) = query.q.as_deref().filter(|q| !q.trim().is_empty()) else {
+        return;
+    };
+    
+    let pattern = contains_pattern(q.trim());
+    if search_contacts {
+        builder
+            .push(" AND (name ILIKE ").push_bind(pattern.clone())
+            .push(" OR contact ILIKE ").push_bind(pattern.clone())
+            .push(" OR email ILIKE ").push_bind(pattern.clone())
+            .push(" OR phone ILIKE ").push_bind(pattern)
+            .push(")");
+    } else {
+        builder.push(" AND name ILIKE ").push_bind(pattern);
+    }
+}
+
+pub async fn create_buyer(pool: &PgPool, request: CreateBuyerRequest, actor: &Actor) -> Result<Buyer> {
+    let mut tx = pool.begin().await?;
+    
+    let buyer = insert_buyer(&mut tx, request, actor).await?;
+    
+    tx.commit().await?;
+    
+    Ok(buyer)
+}
+
+pub(crate) async fn insert_buyer(conn: &mut PgConnection, request: CreateBuyerRequest, actor: &Actor) -> Result<Buyer> {
     let buyer = sqlx::query_as!(
         Buyer,
         r#"
         INSERT INTO buyers (id, name, contact, email, phone)
         VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, name, contact, email, phone, created_at as "created_at!"
+        RETURNING id, name, contact, email, phone, deleted_at, created_at as "created_at!"
         "#,
         Uuid::new_v4(),
         request.name,
@@ -33,7 +89,312 @@ pub async fn create_buyer(pool: &PgPool, request: CreateBuyerRequest) -> Result<
         request.email,
         request.phone
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    record_event(conn, actor, AuditEntity::Buyer, buyer.id, AuditAction::Create, None, Some(&buyer)).await?;
+    
+    Ok(buyer)
+}
+
+pub async fn get_buyer(pool: &PgPool, buyer_id: Uuid) -> Result<Option<Buyer>> {
+    let buyer = sqlx::query_as!(
+        Buyer,
+        r#"
+        SELECT id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        FROM buyers 
+        WHERE id = $1 AND deleted_at IS NULL
+        "#,
+        buyer_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    
+    Ok(buyer)
+}
+
+pub async fn update_buyer(pool: &PgPool, buyer_id: Uuid, request: UpdateBuyerRequest, actor: &Actor) -> Result<Option<Buyer>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_buyer(&mut tx, buyer_id, false).await? else {
+        return Ok(None);
+    };
+    
+    let buyer = sqlx::query_as!(
+        Buyer,
+        r#"
+        UPDATE buyers SET
+            name = COALESCE($2, name),
+            contact = COALESCE($3, contact),
+            email = COALESCE($4, email),
+            phone = COALESCE($5, phone)
+        WHERE id = $1
+        RETURNING id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        "#,
+        buyer_id,
+        request.name,
+        request.contact,
+        request.email,
+        request.phone
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Buyer, buyer_id, AuditAction::Update, Some(&before), Some(&buyer)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(buyer))
+}
+
+/// Folds duplicate buyers into `survivor_id` in one transaction: their PCs, including
+/// trashed ones, move to the survivor, contact details the survivor lacks are copied
+/// from the first duplicate that has them, and the duplicates are deleted.
+/// Returns `None` if the survivor doesn't exist or is in the trash.
+pub async fn merge_buyers(
+    pool: &PgPool,
+    survivor_id: Uuid,
+    duplicate_ids: &[Uuid],
+    actor: &Actor,
+) -> Result<Option<MergeBuyersResult>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_buyer(&mut tx, survivor_id, false).await? else {
+        return Ok(None);
+    };
+    
+    // Duplicates may already be in the trash: a trashed buyer with purchases can't be purged, only merged
+    let duplicates = sqlx::query_as!(
+        Buyer,
+        r#"
+        SELECT id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        FROM buyers 
+        WHERE id = ANY($1)
+        FOR UPDATE
+        "#,
+        duplicate_ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    
+    let mut errors = ValidationErrors::default();
+    for (index, duplicate_id) in duplicate_ids.iter().enumerate() {
+        let field = format!("duplicate_ids[{}]", index);
+        if *duplicate_id == survivor_id {
+            errors.add(field, "is_survivor", "A buyer can't be merged into itself");
+        } else if !duplicates.iter().any(|duplicate| duplicate.id == *duplicate_id) {
+            errors.add(field, "not_found", format!("Buyer {} not found", duplicate_id));
+        } else if duplicate_ids[..index].contains(duplicate_id) {
+            errors.add(field, "repeated", "Each duplicate can only be listed once");
+        }
+    }
+    errors.into_result()?;
+    
+    // Keep the caller's order so "first duplicate with an email" is predictable
+    let ordered: Vec<&Buyer> = duplicate_ids
+        .iter()
+        .filter_map(|id| duplicates.iter().find(|duplicate| duplicate.id == *id))
+        .collect();
+    let first = |field: fn(&Buyer) -> &Option<String>| ordered.iter().find_map(|duplicate| field(duplicate).clone());
+    
+    let buyer = sqlx::query_as!(
+        Buyer,
+        r#"
+        UPDATE buyers SET
+            contact = COALESCE(contact, $2),
+            email = COALESCE(email, $3),
+            phone = COALESCE(phone, $4)
+        WHERE id = $1
+        RETURNING id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        "#,
+        survivor_id,
+        first(|buyer| &buyer.contact),
+        first(|buyer| &buyer.email),
+        first(|buyer| &buyer.phone)
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    record_event(&mut tx, actor, AuditEntity::Buyer, survivor_id, AuditAction::Update, Some(&before), Some(&buyer)).await?;
+    
+    let moved = sqlx::query!(
+        r#"
+        UPDATE pcs SET buyer_id = $1
+        FROM (SELECT id, buyer_id FROM pcs WHERE buyer_id = ANY($2) FOR UPDATE) previous
+        WHERE pcs.id = previous.id
+        RETURNING pcs.id, previous.buyer_id as "previous_buyer_id!"
+        "#,
+        survivor_id,
+        duplicate_ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for pc in &moved {
+        let before = serde_json::json!({ "buyer_id": pc.previous_buyer_id });
+        let after = serde_json::json!({ "buyer_id": survivor_id });
+        record_event(&mut tx, actor, AuditEntity::Pc, pc.id, AuditAction::Update, Some(&before), Some(&after)).await?;
+    }
+    
+    sqlx::query!("DELETE FROM buyers WHERE id = ANY($1)", duplicate_ids)
+        .execute(&mut *tx)
+        .await?;
+    for duplicate in &ordered {
+        record_event(&mut tx, actor, AuditEntity::Buyer, duplicate.id, AuditAction::Merge, Some(*duplicate), Some(&buyer)).await?;
+    }
+    
+    tx.commit().await?;
+    
+    Ok(Some(MergeBuyersResult {
+        buyer,
+        merged_ids: ordered.iter().map(|duplicate| duplicate.id).collect(),
+        pcs_moved: moved.len() as i64,
+    }))
+}
+
+/// Groups buyers sharing a name (case and spacing ignored), an email (case ignored)
+/// or the last eight digits of their phone number, so `+47 123 45 678` matches `12345678`
+pub async fn find_duplicate_buyers(pool: &PgPool) -> Result<Vec<DuplicateBuyerGroup>> {
+    let groups = sqlx::query!(
+        r#"
+        WITH keys AS (
+            SELECT id, 'name' as matched_on, LOWER(REGEXP_REPLACE(TRIM(name), '\s+', ' ', 'g')) as value
+            FROM buyers WHERE deleted_at IS NULL
+            UNION ALL
+            SELECT id, 'email', LOWER(TRIM(email))
+            FROM buyers WHERE deleted_at IS NULL AND email IS NOT NULL
+            UNION ALL
+            SELECT id, 'phone', RIGHT(REGEXP_REPLACE(phone, '\D', '', 'g'), 8)
+            FROM buyers WHERE deleted_at IS NULL AND phone IS NOT NULL
+        )
+        SELECT matched_on as "matched_on!", value as "value!", ARRAY_AGG(id ORDER BY id) as "buyer_ids!"
+        FROM keys
+        WHERE value <> ''
+        GROUP BY matched_on, value
+        HAVING COUNT(*) > 1
+        ORDER BY matched_on, value
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    let buyer_ids: Vec<Uuid> = groups.iter().flat_map(|group| group.buyer_ids.iter().copied()).collect();
+    let buyers = sqlx::query_as!(
+        Buyer,
+        r#"
+        SELECT id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        FROM buyers 
+        WHERE id = ANY($1)
+        ORDER BY created_at, id
+        "#,
+        &buyer_ids
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    let groups = groups
+        .into_iter()
+        .map(|group| DuplicateBuyerGroup {
+            matched_on: match group.matched_on.as_str() {
+                "email" => DuplicateMatch::Email,
+                "phone" => DuplicateMatch::Phone,
+                _ => DuplicateMatch::Name,
+            },
+            value: group.value,
+            buyers: buyers.iter().filter(|buyer| group.buyer_ids.contains(&buyer.id)).cloned().collect(),
+        })
+        .collect();
+    
+    Ok(groups)
+}
+
+/// Moves a buyer to the trash; their purchases keep pointing at them
+pub async fn delete_buyer(pool: &PgPool, buyer_id: Uuid, actor: &Actor) -> Result<bool> {
+    set_buyer_deleted(pool, buyer_id, true, actor).await.map(|buyer| buyer.is_some())
+}
+
+pub async fn get_deleted_buyers(pool: &PgPool) -> Result<Vec<Buyer>> {
+    let buyers = sqlx::query_as!(
+        Buyer,
+        r#"
+        SELECT id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        FROM buyers 
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(buyers)
+}
+
+/// Takes a buyer back out of the trash. Returns `None` if they aren't in the trash.
+pub async fn restore_buyer(pool: &PgPool, buyer_id: Uuid, actor: &Actor) -> Result<Option<Buyer>> {
+    set_buyer_deleted(pool, buyer_id, false, actor).await
+}
+
+/// Permanently deletes a buyer that is already in the trash. Buyers still linked
+/// to a PC, even one in the trash, are refused by the foreign key.
+pub async fn purge_buyer(pool: &PgPool, buyer_id: Uuid, actor: &Actor) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(buyer) = lock_buyer(&mut tx, buyer_id, true).await? else {
+        return Ok(false);
+    };
+    
+    sqlx::query!("DELETE FROM buyers WHERE id = $1", buyer_id)
+        .execute(&mut *tx)
+        .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Buyer, buyer_id, AuditAction::Purge, Some(&buyer), None).await?;
+    
+    tx.commit().await?;
+    
+    Ok(true)
+}
+
+async fn set_buyer_deleted(pool: &PgPool, buyer_id: Uuid, deleted: bool, actor: &Actor) -> Result<Option<Buyer>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_buyer(&mut tx, buyer_id, !deleted).await? else {
+        return Ok(None);
+    };
+    
+    let buyer = sqlx::query_as!(
+        Buyer,
+        r#"
+        UPDATE buyers SET
+            deleted_at = CASE WHEN $2 THEN NOW() END
+        WHERE id = $1
+        RETURNING id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        "#,
+        buyer_id,
+        deleted
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    let action = if deleted { AuditAction::Delete } else { AuditAction::Restore };
+    record_event(&mut tx, actor, AuditEntity::Buyer, buyer_id, action, Some(&before), Some(&buyer)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(buyer))
+}
+
+/// Locks a buyer for the rest of the transaction, finding them only inside or only outside the trash
+async fn lock_buyer(conn: &mut PgConnection, buyer_id: Uuid, in_trash: bool) -> Result<Option<Buyer>> {
+    let buyer = sqlx::query_as!(
+        Buyer,
+        r#"
+        SELECT id, name, contact, email, phone, deleted_at, created_at as "created_at!"
+        FROM buyers 
+        WHERE id = $1 AND (deleted_at IS NOT NULL) = $2
+        FOR UPDATE
+        "#,
+        buyer_id,
+        in_trash
+    )
+    .fetch_optional(&mut *conn)
     .await?;
     
     Ok(buyer)
@@ -46,12 +407,13 @@ pub async fn get_buyer_purchases(pool: &PgPool, buyer_id: Uuid) -> Result<Vec<Pc
         SELECT 
             id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
             buyer_id, platform, platform_reference, intended_price, actual_sale_price,
-            total_cost, profit, profit_percentage, notes,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
             status as "status!: PcStatus", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
         FROM pcs 
-        WHERE buyer_id = $1
+        WHERE buyer_id = $1 AND deleted_at IS NULL
         ORDER BY sale_date DESC
         "#,
         buyer_id
@@ -0,0 +1,124 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::{
+    Actor, AuditAction, AuditEntity, ComponentCategory, ComponentType, CreateComponentCategoryRequest,
+    UpdateComponentCategoryRequest,
+};
+use super::audit_queries::record_event;
+
+/// New categories go after the built-in ones unless given a position
+const DEFAULT_SORT_ORDER: i32 = 500;
+
+pub async fn get_component_categories(pool: &PgPool) -> Result<Vec<ComponentCategory>> {
+    let categories = sqlx::query_as!(
+        ComponentCategory,
+        r#"
+        SELECT
+            id, slug as "slug: ComponentType", name, sort_order, max_per_pc, reorder_point, target_stock,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM component_categories
+        ORDER BY sort_order, slug
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(categories)
+}
+
+pub async fn create_component_category(
+    pool: &PgPool,
+    request: CreateComponentCategoryRequest,
+    actor: &Actor,
+) -> Result<ComponentCategory> {
+    let mut tx = pool.begin().await?;
+    
+    let category = sqlx::query_as!(
+        ComponentCategory,
+        r#"
+        INSERT INTO component_categories (id, slug, name, sort_order, max_per_pc, reorder_point, target_stock)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING
+            id, slug as "slug: ComponentType", name, sort_order, max_per_pc, reorder_point, target_stock,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        Uuid::new_v4(),
+        request.slug.as_str(),
+        request.name.trim(),
+        request.sort_order.unwrap_or(DEFAULT_SORT_ORDER),
+        request.max_per_pc,
+        request.reorder_point,
+        request.target_stock
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Category, category.id, AuditAction::Create, None, Some(&category)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(category)
+}
+
+pub async fn update_component_category(
+    pool: &PgPool,
+    slug: &ComponentType,
+    request: UpdateComponentCategoryRequest,
+    actor: &Actor,
+) -> Result<Option<ComponentCategory>> {
+    let mut tx = pool.begin().await?;
+    
+    let before = sqlx::query_as!(
+        ComponentCategory,
+        r#"
+        SELECT
+            id, slug as "slug: ComponentType", name, sort_order, max_per_pc, reorder_point, target_stock,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM component_categories
+        WHERE slug = $1
+        FOR UPDATE
+        "#,
+        slug.as_str()
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(before) = before else {
+        return Ok(None);
+    };
+    
+    let category = sqlx::query_as!(
+        ComponentCategory,
+        r#"
+        UPDATE component_categories SET
+            name = COALESCE($2, name),
+            sort_order = COALESCE($3, sort_order),
+            max_per_pc = COALESCE($4, max_per_pc),
+            reorder_point = COALESCE($5, reorder_point),
+            target_stock = COALESCE($6, target_stock)
+        WHERE id = $1
+        RETURNING
+            id, slug as "slug: ComponentType", name, sort_order, max_per_pc, reorder_point, target_stock,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        before.id,
+        request.name.as_deref().map(str::trim),
+        request.sort_order,
+        request.max_per_pc,
+        request.reorder_point,
+        request.target_stock
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Category, category.id, AuditAction::Update, Some(&before), Some(&category)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(category))
+}
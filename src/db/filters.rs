@@ -0,0 +1,29 @@
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::models::SortOrder;
+
+/// `ILIKE` pattern matching `text` anywhere, with wildcards in the input escaped
+pub(crate) fn contains_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Appends `ORDER BY`, a stable tie-breaker on `id`, and `LIMIT`/`OFFSET` for a 1-based page
+pub(crate) fn push_order_and_page(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    columns: &[&'static str],
+    order: SortOrder,
+    page: i64,
+    per_page: i64,
+) {
+    builder.push(" ORDER BY ");
+    for column in columns {
+        builder.push(format!("{} {} NULLS LAST, ", column, order.as_sql()));
+    }
+    builder.push(format!("id {}", order.as_sql()));
+    builder.push(" LIMIT ").push_bind(per_page);
+    builder.push(" OFFSET ").push_bind((page - 1) * per_page);
+}
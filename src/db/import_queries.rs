@@ -0,0 +1,220 @@
+use sqlx::{Acquire, PgConnection, PgPool};
+use uuid::Uuid;
+use anyhow::Result;
+
+use crate::models::{
+    Actor, AuditAction, AuditEntity, CreateBuyerRequest, CreateInventoryPartRequest, ImportRecord,
+    ImportReport, ImportRow, ImportRowResult, ImportedPc, PcStatus, RowOutcome,
+};
+use crate::validation::Validate;
+use super::audit_queries::record_event;
+use super::buyer_queries::insert_buyer;
+use super::inventory_queries::insert_part;
+use super::pc_queries::{fetch_pc, insert_component, record_price_change, record_status_change};
+
+/// Status history reason for every step an imported PC is walked through
+const IMPORT_REASON: &str = "Imported from spreadsheet";
+
+enum Written {
+    Imported { buyer_created: bool },
+    Skipped(&'static str),
+}
+
+/// Writes the rows in one transaction that is only committed when no row
+/// fails and this isn't a dry run. Each row runs in its own savepoint so a
+/// database error in one row doesn't abort the transaction and the remaining
+/// rows are still checked, including against rows earlier in the same file.
+pub async fn import_rows(pool: &PgPool, rows: Vec<ImportRow>, dry_run: bool, actor: &Actor) -> Result<ImportReport> {
+    let mut tx = pool.begin().await?;
+    let mut report = ImportReport { dry_run, ..ImportReport::default() };
+
+    for ImportRow { sheet, row, name, record } in rows {
+        let written = match record {
+            Ok(record) => {
+                let mut savepoint = tx.begin().await?;
+                match write_record(&mut savepoint, record, actor).await {
+                    Ok(written) => {
+                        savepoint.commit().await?;
+                        Ok(written)
+                    }
+                    Err(error) => {
+                        savepoint.rollback().await?;
+                        Err(error.to_string())
+                    }
+                }
+            }
+            Err(message) => Err(message),
+        };
+
+        let (outcome, message) = match written {
+            Ok(Written::Imported { buyer_created }) => {
+                report.buyers_created += usize::from(buyer_created);
+                (RowOutcome::Imported, None)
+            }
+            Ok(Written::Skipped(reason)) => (RowOutcome::Skipped, Some(reason.to_string())),
+            Err(message) => (RowOutcome::Failed, Some(message)),
+        };
+        report.push(ImportRowResult { sheet, row, outcome, name, message });
+    }
+
+    if report.failed == 0 && !dry_run {
+        tx.commit().await?;
+        report.committed = true;
+    } else {
+        tx.rollback().await?;
+    }
+
+    Ok(report)
+}
+
+async fn write_record(conn: &mut PgConnection, record: ImportRecord, actor: &Actor) -> Result<Written> {
+    match record {
+        ImportRecord::Part(request) => import_part(conn, request, actor).await,
+        ImportRecord::Pc(pc) => import_pc(conn, pc, actor).await,
+        ImportRecord::Buyer(request) => import_buyer(conn, request, actor).await,
+    }
+}
+
+/// Parts already in inventory under the same type and name are left alone
+async fn import_part(conn: &mut PgConnection, request: CreateInventoryPartRequest, actor: &Actor) -> Result<Written> {
+    request.validate()?;
+
+    let exists = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM parts_inventory
+            WHERE component_type = $1
+              AND LOWER(component_name) = LOWER($2)
+              AND deleted_at IS NULL
+        ) as "exists!"
+        "#,
+        request.component_type.as_str(),
+        request.component_name
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    if exists {
+        return Ok(Written::Skipped("A part with this type and name is already in inventory"));
+    }
+
+    insert_part(conn, request, actor).await?;
+
+    Ok(Written::Imported { buyer_created: false })
+}
+
+/// Creates the PC with its components and walks it through the same status
+/// history a PC built, listed and sold in the app would have
+async fn import_pc(conn: &mut PgConnection, pc: ImportedPc, actor: &Actor) -> Result<Written> {
+    pc.validate()?;
+
+    // Names are unique across the trash too
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM pcs WHERE pc_name = $1) as "exists!""#,
+        pc.pc_name
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    if exists {
+        return Ok(Written::Skipped("A PC with this name already exists"));
+    }
+
+    let (buyer_id, buyer_created) = match pc.buyer {
+        Some(buyer) => {
+            let (id, created) = find_or_create_buyer(conn, buyer, actor).await?;
+            (Some(id), created)
+        }
+        None => (None, false),
+    };
+
+    let mut steps = vec![PcStatus::Building];
+    if pc.list_date.is_some() || pc.sale_date.is_some() {
+        steps.push(PcStatus::Listed);
+    }
+    if pc.sale_date.is_some() {
+        steps.push(PcStatus::Sold);
+    }
+    if pc.archived {
+        steps.push(PcStatus::Archived);
+    }
+    let status = *steps.last().unwrap_or(&PcStatus::Building);
+
+    let pc_id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO pcs (
+            id, pc_name, build_date, list_date, sale_date, buyer_id, platform,
+            platform_reference, intended_price, actual_sale_price, notes, status
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        "#,
+        pc_id,
+        pc.pc_name,
+        pc.build_date,
+        pc.list_date,
+        pc.sale_date,
+        buyer_id,
+        pc.platform,
+        pc.platform_reference,
+        pc.intended_price,
+        pc.actual_sale_price,
+        pc.notes,
+        status as PcStatus
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let mut from = None;
+    for step in steps {
+        record_status_change(conn, pc_id, from, step, actor, Some(IMPORT_REASON)).await?;
+        from = Some(step);
+    }
+    record_price_change(conn, pc_id, None, pc.intended_price, false, actor, Some(IMPORT_REASON)).await?;
+
+    for component in pc.components {
+        insert_component(conn, pc_id, component, actor).await?;
+    }
+
+    let pc = fetch_pc(conn, pc_id).await?;
+    record_event(conn, actor, AuditEntity::Pc, pc_id, AuditAction::Create, None, Some(&pc)).await?;
+
+    Ok(Written::Imported { buyer_created })
+}
+
+/// Buyers sharing a name with a live buyer are taken to be the same person
+async fn import_buyer(conn: &mut PgConnection, request: CreateBuyerRequest, actor: &Actor) -> Result<Written> {
+    request.validate()?;
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM buyers WHERE LOWER(name) = LOWER($1) AND deleted_at IS NULL) as "exists!""#,
+        request.name.trim()
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    if exists {
+        return Ok(Written::Skipped("A buyer with this name already exists"));
+    }
+
+    insert_buyer(conn, request, actor).await?;
+
+    Ok(Written::Imported { buyer_created: true })
+}
+
+/// Buyers are matched by name, ignoring case; the first match wins
+async fn find_or_create_buyer(conn: &mut PgConnection, request: CreateBuyerRequest, actor: &Actor) -> Result<(Uuid, bool)> {
+    let existing = sqlx::query_scalar!(
+        r#"
+        SELECT id FROM buyers
+        WHERE LOWER(name) = LOWER($1) AND deleted_at IS NULL
+        ORDER BY created_at, id
+        LIMIT 1
+        "#,
+        request.name.trim()
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match existing {
+        Some(id) => Ok((id, false)),
+        None => Ok((insert_buyer(conn, request, actor).await?.id, true)),
+    }
+}
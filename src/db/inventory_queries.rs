@@ -1,19 +1,72 @@
-use sqlx::PgPool;
+use std::fmt;
+
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 use anyhow::Result;
+use rust_decimal::Decimal;
+
+use crate::models::{ComponentType, CostingMethod, InventoryLot, InventorySettings, InventorySettingsRequest, InventoryPart, PartPriceChange, PartPriceType, CreateInventoryPartRequest, UpdateInventoryPartRequest, CreateStockMovementRequest, LowStockPart, StockMovement, StockMovementType, StockDiscrepancy, Page, PartListQuery, SortOrder, page_bounds, Actor, AuditAction, AuditEntity};
+use crate::costing::{self, ShelfLot};
+use crate::reorder::DEFAULT_REORDER_POINT;
+use super::audit_queries::record_event;
+use super::filters::{contains_pattern, push_order_and_page};
 
-use crate::models::{InventoryPart, CreateInventoryPartRequest, UpdateInventoryPartRequest};
+/// A stock movement would take more parts than are on the shelf
+#[derive(Debug)]
+pub struct InsufficientStockError {
+    pub available: i32,
+}
+
+impl fmt::Display for InsufficientStockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Not enough stock: {} available", self.available)
+    }
+}
+
+impl std::error::Error for InsufficientStockError {}
+
+/// What a stock movement records besides the part and who made it
+pub(crate) struct StockChange<'a> {
+    pub movement_type: StockMovementType,
+    /// Signed: positive adds stock
+    pub quantity: i32,
+    pub pc_id: Option<Uuid>,
+    pub purchase_order_id: Option<Uuid>,
+    /// The lot parts coming back from a build return to
+    pub lot_id: Option<Uuid>,
+    /// Cost per part added; defaults to the part's buy-in price
+    pub unit_cost: Option<Decimal>,
+    pub reason: Option<&'a str>,
+    pub reference: Option<&'a str>,
+}
+
+impl StockChange<'_> {
+    /// A movement into or out of a build
+    pub(crate) fn build(movement_type: StockMovementType, quantity: i32, pc_id: Uuid) -> Self {
+        StockChange {
+            movement_type,
+            quantity,
+            pc_id: Some(pc_id),
+            purchase_order_id: None,
+            lot_id: None,
+            unit_cost: None,
+            reason: None,
+            reference: None,
+        }
+    }
+}
 
 pub async fn get_all_parts(pool: &PgPool) -> Result<Vec<InventoryPart>> {
     let parts = sqlx::query_as!(
         InventoryPart,
         r#"
         SELECT 
-            id, component_type, component_name, buy_in_price, typical_sell_price,
-            quantity_available as "quantity_available!", notes, purchase_link, 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         FROM parts_inventory 
+        WHERE deleted_at IS NULL
         ORDER BY component_type, component_name
         "#
     )
@@ -23,37 +76,120 @@ pub async fn get_all_parts(pool: &PgPool) -> Result<Vec<InventoryPart>> {
     Ok(parts)
 }
 
-pub async fn create_part(pool: &PgPool, request: CreateInventoryPartRequest) -> Result<InventoryPart> {
+pub async fn list_parts(pool: &PgPool, query: &PartListQuery) -> Result<Page<InventoryPart>> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+    
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM parts_inventory WHERE deleted_at IS NULL");
+    push_part_filters(&mut count, query);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+    
+    let mut select = QueryBuilder::new(
+        r#"
+        SELECT 
+            id, component_type, component_name, buy_in_price, typical_sell_price,
+            quantity_available, reorder_point, target_stock, notes, purchase_link, deleted_at, created_at, updated_at
+        FROM parts_inventory 
+        WHERE deleted_at IS NULL
+        "#
+    );
+    push_part_filters(&mut select, query);
+    push_order_and_page(
+        &mut select,
+        query.sort.unwrap_or_default().columns(),
+        query.order.unwrap_or(SortOrder::Asc),
+        page,
+        per_page,
+    );
+    let items = select.build_query_as::<InventoryPart>().fetch_all(pool).await?;
+    
+    Ok(Page { items, total, page, per_page })
+}
+
+fn push_part_filters(builder: &mut QueryBuilder<'_, Postgres>, query: &PartListQuery) {
+    if let Some(component_type) = &query.component_type {
+        builder.push(" AND component_type = ").push_bind(component_type.clone());
+    }
+    match query.in_stock {
+        Some(true) => { builder.push(" AND quantity_available > 0"); }
+        Some(false) => { builder.push(" AND quantity_available = 0"); }
+        None => {}
+    }
+    if let Some(min_price) = query.min_price {
+        builder.push(" AND buy_in_price >= ").push_bind(min_price);
+    }
+    if let Some(max_price) = query.max_price {
+        builder.push(" AND buy_in_price <= ").push_bind(max_price);
+    }
+    if let Some(q) = query.q.as_deref().filter(|q| !q.trim().is_empty()) {
+        builder.push(" AND component_name ILIKE ").push_bind(contains_pattern(q.trim()));
+    }
+}
+
+pub async fn create_part(pool: &PgPool, request: CreateInventoryPartRequest, actor: &Actor) -> Result<InventoryPart> {
+    let mut tx = pool.begin().await?;
+    
+    let part = insert_part(&mut tx, request, actor).await?;
+    
+    tx.commit().await?;
+    
+    Ok(part)
+}
+
+pub(crate) async fn insert_part(conn: &mut PgConnection, request: CreateInventoryPartRequest, actor: &Actor) -> Result<InventoryPart> {
     let part = sqlx::query_as!(
         InventoryPart,
         r#"
         INSERT INTO parts_inventory (
             id, component_type, component_name, buy_in_price, typical_sell_price,
-            quantity_available, notes, purchase_link
+            quantity_available, reorder_point, target_stock, notes, purchase_link
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING 
-            id, component_type, component_name, buy_in_price, typical_sell_price,
-            quantity_available as "quantity_available!", notes, purchase_link, 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
         Uuid::new_v4(),
-        request.component_type,
+        request.component_type.as_str(),
         request.component_name,
         request.buy_in_price,
         request.typical_sell_price,
         request.quantity_available.unwrap_or(0),
+        request.reorder_point,
+        request.target_stock,
         request.notes,
         request.purchase_link
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await?;
     
+    record_event(conn, actor, AuditEntity::Part, part.id, AuditAction::Create, None, Some(&part)).await?;
+    record_price_changes(conn, None, &part, actor, None).await?;
+    if part.quantity_available > 0 {
+        let change = StockChange {
+            movement_type: StockMovementType::Receive,
+            quantity: part.quantity_available,
+            pc_id: None,
+            purchase_order_id: None,
+            lot_id: None,
+            unit_cost: None,
+            reason: None,
+            reference: None,
+        };
+        record_stock_movement(conn, part.id, change, actor).await?;
+    }
+    
     Ok(part)
 }
 
-pub async fn update_part(pool: &PgPool, part_id: Uuid, request: UpdateInventoryPartRequest) -> Result<Option<InventoryPart>> {
+pub async fn update_part(pool: &PgPool, part_id: Uuid, request: UpdateInventoryPartRequest, actor: &Actor) -> Result<Option<InventoryPart>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_part(&mut tx, part_id, false).await? else {
+        return Ok(None);
+    };
+    
     let part = sqlx::query_as!(
         InventoryPart,
         r#"
@@ -64,11 +200,13 @@ pub async fn update_part(pool: &PgPool, part_id: Uuid, request: UpdateInventoryP
             quantity_available = COALESCE($5, quantity_available),
             notes = COALESCE($6, notes),
             purchase_link = COALESCE($7, purchase_link),
+            reorder_point = COALESCE($8, reorder_point),
+            target_stock = COALESCE($9, target_stock),
             updated_at = NOW()
         WHERE id = $1
         RETURNING 
-            id, component_type, component_name, buy_in_price, typical_sell_price,
-            quantity_available as "quantity_available!", notes, purchase_link, 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
@@ -78,42 +216,612 @@ pub async fn update_part(pool: &PgPool, part_id: Uuid, request: UpdateInventoryP
         request.typical_sell_price,
         request.quantity_available,
         request.notes,
-        request.purchase_link
+        request.purchase_link,
+        request.reorder_point,
+        request.target_stock
     )
-    .fetch_optional(pool)
+    .fetch_one(&mut *tx)
     .await?;
     
-    Ok(part)
+    record_event(&mut tx, actor, AuditEntity::Part, part_id, AuditAction::Update, Some(&before), Some(&part)).await?;
+    record_price_changes(&mut tx, Some(&before), &part, actor, request.price_change_reason.as_deref()).await?;
+    // Setting the quantity directly counts as a stock correction
+    if part.quantity_available != before.quantity_available {
+        let change = StockChange {
+            movement_type: StockMovementType::Adjust,
+            quantity: part.quantity_available - before.quantity_available,
+            pc_id: None,
+            purchase_order_id: None,
+            lot_id: None,
+            unit_cost: None,
+            reason: None,
+            reference: None,
+        };
+        record_stock_movement(&mut tx, part_id, change, actor).await?;
+    }
+    
+    tx.commit().await?;
+    
+    Ok(Some(part))
 }
 
-pub async fn delete_part(pool: &PgPool, part_id: Uuid) -> Result<bool> {
-    let result = sqlx::query!(
-        "DELETE FROM parts_inventory WHERE id = $1",
+/// Price changes of a part, oldest first, including while it is in the trash.
+/// Returns `None` if the part doesn't exist.
+pub async fn get_part_price_history(pool: &PgPool, part_id: Uuid) -> Result<Option<Vec<PartPriceChange>>> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM parts_inventory WHERE id = $1) as "exists!""#,
+        part_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+    
+    let history = sqlx::query_as!(
+        PartPriceChange,
+        r#"
+        SELECT
+            id, part_id, price_type as "price_type: PartPriceType",
+            old_price, new_price, changed_by, reason, changed_at
+        FROM part_price_changes
+        WHERE part_id = $1
+        ORDER BY changed_at, price_type, id
+        "#,
         part_id
     )
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
     
-    Ok(result.rows_affected() > 0)
+    Ok(Some(history))
 }
 
-pub async fn get_low_stock_parts(pool: &PgPool, threshold: i32) -> Result<Vec<InventoryPart>> {
+/// Records a movement by hand and moves the part's stock with it. Returns
+/// `None` if the part doesn't exist or is in the trash.
+pub async fn create_stock_movement(pool: &PgPool, part_id: Uuid, request: CreateStockMovementRequest, actor: &Actor) -> Result<Option<StockMovement>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_part(&mut tx, part_id, false).await? else {
+        return Ok(None);
+    };
+    
+    let quantity = if request.movement_type.removes_stock() { -request.quantity } else { request.quantity };
+    if before.quantity_available + quantity < 0 {
+        return Err(InsufficientStockError { available: before.quantity_available }.into());
+    }
+    
+    let part = sqlx::query_as!(
+        InventoryPart,
+        r#"
+        UPDATE parts_inventory SET
+            quantity_available = quantity_available + $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        part_id,
+        quantity
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Part, part_id, AuditAction::Update, Some(&before), Some(&part)).await?;
+    let change = StockChange {
+        movement_type: request.movement_type,
+        quantity,
+        pc_id: None,
+        purchase_order_id: None,
+        lot_id: None,
+        unit_cost: request.unit_cost,
+        reason: request.reason.as_deref(),
+        reference: request.reference.as_deref(),
+    };
+    let movement = record_stock_movement(&mut tx, part_id, change, actor).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(movement))
+}
+
+/// A part's stock movements, oldest first. Returns `None` if the part doesn't exist.
+pub async fn get_stock_movements(pool: &PgPool, part_id: Uuid) -> Result<Option<Vec<StockMovement>>> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM parts_inventory WHERE id = $1) as "exists!""#,
+        part_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+    
+    let movements = sqlx::query_as!(
+        StockMovement,
+        r#"
+        SELECT
+            id, part_id, movement_type as "movement_type: StockMovementType",
+            quantity, balance_after, pc_id, purchase_order_id, lot_id, unit_cost,
+            reason, reference, created_by, created_at
+        FROM stock_movements
+        WHERE part_id = $1
+        ORDER BY created_at, id
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(Some(movements))
+}
+
+/// Parts, trashed ones included, whose `quantity_available` isn't the sum of
+/// their stock movements
+pub async fn get_stock_discrepancies(pool: &PgPool) -> Result<Vec<StockDiscrepancy>> {
+    let discrepancies = sqlx::query_as!(
+        StockDiscrepancy,
+        r#"
+        SELECT
+            p.id as part_id,
+            p.component_type as "component_type: ComponentType",
+            p.component_name,
+            p.quantity_available as "quantity_available!",
+            ledger.quantity as "ledger_quantity!",
+            p.quantity_available - ledger.quantity as "difference!"
+        FROM parts_inventory p
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(SUM(m.quantity), 0)::int as quantity
+            FROM stock_movements m
+            WHERE m.part_id = p.id
+        ) ledger
+        WHERE p.quantity_available <> ledger.quantity
+        ORDER BY p.component_type, p.component_name
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(discrepancies)
+}
+
+/// Moves a part to the trash; components built from it keep their link and cost
+pub async fn delete_part(pool: &PgPool, part_id: Uuid, actor: &Actor) -> Result<bool> {
+    set_part_deleted(pool, part_id, true, actor).await.map(|part| part.is_some())
+}
+
+pub async fn get_deleted_parts(pool: &PgPool) -> Result<Vec<InventoryPart>> {
     let parts = sqlx::query_as!(
         InventoryPart,
         r#"
         SELECT 
-            id, component_type, component_name, buy_in_price, typical_sell_price,
-            quantity_available as "quantity_available!", notes, purchase_link, 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         FROM parts_inventory 
-        WHERE quantity_available <= $1
-        ORDER BY quantity_available ASC, component_type, component_name
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(parts)
+}
+
+/// Takes a part back out of the trash. Returns `None` if it isn't in the trash.
+pub async fn restore_part(pool: &PgPool, part_id: Uuid, actor: &Actor) -> Result<Option<InventoryPart>> {
+    set_part_deleted(pool, part_id, false, actor).await
+}
+
+/// Permanently deletes a part that is already in the trash. Components built
+/// from it keep their cost but lose the link.
+pub async fn purge_part(pool: &PgPool, part_id: Uuid, actor: &Actor) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(part) = lock_part(&mut tx, part_id, true).await? else {
+        return Ok(false);
+    };
+    
+    sqlx::query!("DELETE FROM parts_inventory WHERE id = $1", part_id)
+        .execute(&mut *tx)
+        .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Part, part_id, AuditAction::Purge, Some(&part), None).await?;
+    
+    tx.commit().await?;
+    
+    Ok(true)
+}
+
+async fn set_part_deleted(pool: &PgPool, part_id: Uuid, deleted: bool, actor: &Actor) -> Result<Option<InventoryPart>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_part(&mut tx, part_id, !deleted).await? else {
+        return Ok(None);
+    };
+    
+    let part = sqlx::query_as!(
+        InventoryPart,
+        r#"
+        UPDATE parts_inventory SET
+            deleted_at = CASE WHEN $2 THEN NOW() END
+        WHERE id = $1
+        RETURNING 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
         "#,
-        threshold
+        part_id,
+        deleted
     )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    let action = if deleted { AuditAction::Delete } else { AuditAction::Restore };
+    record_event(&mut tx, actor, AuditEntity::Part, part_id, action, Some(&before), Some(&part)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(part))
+}
+
+/// Locks a part for the rest of the transaction, finding it only inside or only outside the trash
+async fn lock_part(conn: &mut PgConnection, part_id: Uuid, in_trash: bool) -> Result<Option<InventoryPart>> {
+    let part = sqlx::query_as!(
+        InventoryPart,
+        r#"
+        SELECT 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM parts_inventory 
+        WHERE id = $1 AND (deleted_at IS NOT NULL) = $2
+        FOR UPDATE
+        "#,
+        part_id,
+        in_trash
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    Ok(part)
+}
+
+/// Parts at or below `threshold`, or else their own reorder point, their
+/// category's or the default, emptiest first
+pub async fn get_low_stock_parts(pool: &PgPool, threshold: Option<i32>) -> Result<Vec<LowStockPart>> {
+    let parts = sqlx::query_as::<_, LowStockPart>(
+        r#"
+        SELECT
+            p.*,
+            COALESCE($1, p.reorder_point, c.reorder_point, $2) as effective_reorder_point,
+            COALESCE(p.target_stock, c.target_stock) as effective_target_stock
+        FROM parts_inventory p
+        JOIN component_categories c ON c.slug = p.component_type
+        WHERE p.deleted_at IS NULL
+            AND p.quantity_available <= COALESCE($1, p.reorder_point, c.reorder_point, $2)
+        ORDER BY p.quantity_available ASC, p.component_type, p.component_name
+        "#,
+    )
+    .bind(threshold)
+    .bind(DEFAULT_REORDER_POINT)
     .fetch_all(pool)
     .await?;
     
     Ok(parts)
-}
\ No newline at end of file
+}
+
+/// Records each of the part's prices that differs between `before` and `after`;
+/// `before` is `None` for a new part
+async fn record_price_changes(
+    conn: &mut PgConnection,
+    before: Option<&InventoryPart>,
+    after: &InventoryPart,
+    actor: &Actor,
+    reason: Option<&str>,
+) -> Result<()> {
+    let prices = [
+        (PartPriceType::BuyIn, before.and_then(|part| part.buy_in_price), after.buy_in_price),
+        (PartPriceType::TypicalSell, before.and_then(|part| part.typical_sell_price), after.typical_sell_price),
+    ];
+    
+    for (price_type, old_price, new_price) in prices {
+        if old_price == new_price {
+            continue;
+        }
+        sqlx::query!(
+            r#"
+            INSERT INTO part_price_changes (part_id, price_type, old_price, new_price, changed_by, reason)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            after.id,
+            price_type as PartPriceType,
+            old_price,
+            new_price,
+            actor.name,
+            reason
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    Ok(())
+}
+
+/// Puts parts that arrived from a supplier on the shelf in a lot of their own
+/// and makes their landed cost, the change's `unit_cost`, the part's buy-in price
+pub(crate) async fn receive_stock(conn: &mut PgConnection, part_id: Uuid, change: StockChange<'_>, actor: &Actor) -> Result<()> {
+    let before = sqlx::query_as!(
+        InventoryPart,
+        r#"
+        SELECT 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM parts_inventory 
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        part_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    let part = sqlx::query_as!(
+        InventoryPart,
+        r#"
+        UPDATE parts_inventory SET
+            quantity_available = quantity_available + $2,
+            buy_in_price = COALESCE($3, buy_in_price),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING 
+            id, component_type as "component_type: ComponentType", component_name, buy_in_price, typical_sell_price,
+            quantity_available as "quantity_available!", reorder_point, target_stock, notes, purchase_link, deleted_at,
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        part_id,
+        change.quantity,
+        change.unit_cost
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    record_event(conn, actor, AuditEntity::Part, part_id, AuditAction::Update, Some(&before), Some(&part)).await?;
+    record_price_changes(conn, Some(&before), &part, actor, change.reason).await?;
+    record_stock_movement(conn, part_id, change, actor).await?;
+    
+    Ok(())
+}
+
+/// Adds a movement to a part's ledger and moves its lots with it. Change
+/// `quantity_available` first, in the same transaction; the movement reads its
+/// balance from there.
+pub(crate) async fn record_stock_movement(conn: &mut PgConnection, part_id: Uuid, change: StockChange<'_>, actor: &Actor) -> Result<StockMovement> {
+    let (lot_id, unit_cost) = if change.quantity > 0 {
+        let lot = add_to_lot(conn, part_id, &change).await?;
+        (Some(lot.id), lot.unit_cost)
+    } else {
+        take_from_lots(conn, part_id, -change.quantity).await?
+    };
+    
+    let movement = sqlx::query_as!(
+        StockMovement,
+        r#"
+        INSERT INTO stock_movements (
+            part_id, movement_type, quantity, balance_after, pc_id, purchase_order_id,
+            lot_id, unit_cost, reason, reference, created_by
+        )
+        SELECT $1, $2, $3, quantity_available, $4, $5, $6, $7, $8, $9, $10
+        FROM parts_inventory
+        WHERE id = $1
+        RETURNING
+            id, part_id, movement_type as "movement_type: StockMovementType",
+            quantity, balance_after, pc_id, purchase_order_id, lot_id, unit_cost,
+            reason, reference, created_by, created_at
+        "#,
+        part_id,
+        change.movement_type as StockMovementType,
+        change.quantity,
+        change.pc_id,
+        change.purchase_order_id,
+        lot_id,
+        unit_cost,
+        change.reason,
+        change.reference,
+        actor.name
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(movement)
+}
+
+/// Puts parts back in the lot the change names if they fit, or else in a new
+/// lot at the change's unit cost or the part's buy-in price
+async fn add_to_lot(conn: &mut PgConnection, part_id: Uuid, change: &StockChange<'_>) -> Result<InventoryLot> {
+    if let Some(lot_id) = change.lot_id {
+        let lot = sqlx::query_as!(
+            InventoryLot,
+            r#"
+            UPDATE inventory_lots SET
+                quantity_remaining = quantity_remaining + $3
+            WHERE id = $1 AND part_id = $2 AND quantity_remaining + $3 <= quantity_received
+            RETURNING id, part_id, purchase_order_id, unit_cost, quantity_received, quantity_remaining, received_at
+            "#,
+            lot_id,
+            part_id,
+            change.quantity
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        if let Some(lot) = lot {
+            return Ok(lot);
+        }
+    }
+    
+    let lot = sqlx::query_as!(
+        InventoryLot,
+        r#"
+        INSERT INTO inventory_lots (part_id, purchase_order_id, unit_cost, quantity_received, quantity_remaining)
+        SELECT $1, $2, COALESCE($3, buy_in_price), $4, $4
+        FROM parts_inventory
+        WHERE id = $1
+        RETURNING id, part_id, purchase_order_id, unit_cost, quantity_received, quantity_remaining, received_at
+        "#,
+        part_id,
+        change.purchase_order_id,
+        change.unit_cost,
+        change.quantity
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(lot)
+}
+
+/// Takes `quantity` parts from the part's lots, oldest first, and costs them by
+/// the costing method, at the buy-in price if no lot has a cost. Returns the
+/// lot when they all came from one.
+async fn take_from_lots(conn: &mut PgConnection, part_id: Uuid, quantity: i32) -> Result<(Option<Uuid>, Option<Decimal>)> {
+    let method = fetch_inventory_settings(conn).await?.costing_method;
+    let lots = sqlx::query_as!(
+        ShelfLot,
+        r#"
+        SELECT id, unit_cost, quantity_remaining
+        FROM inventory_lots
+        WHERE part_id = $1 AND quantity_remaining > 0
+        ORDER BY received_at, id
+        FOR UPDATE
+        "#,
+        part_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    
+    let taken = costing::take_from_lots(method, &lots, quantity);
+    for (lot_id, count) in &taken.lots {
+        sqlx::query!(
+            "UPDATE inventory_lots SET quantity_remaining = quantity_remaining - $2 WHERE id = $1",
+            lot_id,
+            count
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    let unit_cost = match taken.unit_cost {
+        Some(cost) => Some(cost),
+        None => sqlx::query_scalar!("SELECT buy_in_price FROM parts_inventory WHERE id = $1", part_id)
+            .fetch_one(&mut *conn)
+            .await?,
+    };
+    let lot_id = match taken.lots[..] {
+        [(lot_id, _)] => Some(lot_id),
+        _ => None,
+    };
+    
+    Ok((lot_id, unit_cost))
+}
+
+/// A part's lots, oldest first, including used-up ones. Returns `None` if the
+/// part doesn't exist.
+pub async fn get_part_lots(pool: &PgPool, part_id: Uuid) -> Result<Option<Vec<InventoryLot>>> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM parts_inventory WHERE id = $1) as "exists!""#,
+        part_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+    
+    let lots = sqlx::query_as!(
+        InventoryLot,
+        r#"
+        SELECT id, part_id, purchase_order_id, unit_cost, quantity_received, quantity_remaining, received_at
+        FROM inventory_lots
+        WHERE part_id = $1
+        ORDER BY received_at, id
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(Some(lots))
+}
+
+pub async fn get_inventory_settings(pool: &PgPool) -> Result<InventorySettings> {
+    let mut conn = pool.acquire().await?;
+    
+    fetch_inventory_settings(&mut conn).await
+}
+
+/// Changes how parts are costed from now on; components already built keep their cost
+pub async fn update_inventory_settings(pool: &PgPool, request: InventorySettingsRequest, actor: &Actor) -> Result<InventorySettings> {
+    let mut tx = pool.begin().await?;
+    
+    let before = sqlx::query_as!(
+        InventorySettings,
+        r#"
+        SELECT
+            id, costing_method as "costing_method: CostingMethod",
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM inventory_settings
+        FOR UPDATE
+        "#
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    let settings = sqlx::query_as!(
+        InventorySettings,
+        r#"
+        UPDATE inventory_settings SET
+            costing_method = $2
+        WHERE id = $1
+        RETURNING
+            id, costing_method as "costing_method: CostingMethod",
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        before.id,
+        request.costing_method as CostingMethod
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Settings, settings.id, AuditAction::Update, Some(&before), Some(&settings)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(settings)
+}
+
+async fn fetch_inventory_settings(conn: &mut PgConnection) -> Result<InventorySettings> {
+    let settings = sqlx::query_as!(
+        InventorySettings,
+        r#"
+        SELECT
+            id, costing_method as "costing_method: CostingMethod",
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM inventory_settings
+        "#
+    )
+    .fetch_one(conn)
+    .await?;
+    
+    Ok(settings)
+}
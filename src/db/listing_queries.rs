@@ -0,0 +1,188 @@
+use chrono::NaiveDate;
+use sqlx::{FromRow, PgConnection, PgPool};
+use anyhow::Result;
+
+use crate::models::{Actor, AuditAction, AuditEntity, ListingSettings, ListingSettingsRequest, Pc, PcPriceChange, PcStatus, StaleListing};
+use crate::pricing;
+use super::audit_queries::record_event;
+use super::pc_queries::record_price_change;
+
+/// A listed PC with when the schedule last dropped its price during the
+/// current listing
+#[derive(FromRow)]
+struct ListingRow {
+    #[sqlx(flatten)]
+    pc: Pc,
+    last_drop: Option<NaiveDate>,
+}
+
+pub async fn get_listing_settings(pool: &PgPool) -> Result<ListingSettings> {
+    let mut conn = pool.acquire().await?;
+    
+    fetch_settings(&mut conn).await
+}
+
+pub async fn update_listing_settings(pool: &PgPool, request: ListingSettingsRequest, actor: &Actor) -> Result<ListingSettings> {
+    let mut tx = pool.begin().await?;
+    
+    let before = sqlx::query_as!(
+        ListingSettings,
+        r#"
+        SELECT
+            id, stale_after_days, price_drop_percent, price_drop_interval_days, min_margin,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM listing_settings
+        FOR UPDATE
+        "#
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    let settings = sqlx::query_as!(
+        ListingSettings,
+        r#"
+        UPDATE listing_settings SET
+            stale_after_days = $2,
+            price_drop_percent = $3,
+            price_drop_interval_days = $4,
+            min_margin = $5
+        WHERE id = $1
+        RETURNING
+            id, stale_after_days, price_drop_percent, price_drop_interval_days, min_margin,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        before.id,
+        request.stale_after_days,
+        request.price_drop_percent,
+        request.price_drop_interval_days,
+        request.min_margin
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Settings, settings.id, AuditAction::Update, Some(&before), Some(&settings)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(settings)
+}
+
+/// Listed PCs on the market for at least `days`, or the configured threshold,
+/// longest-listed first
+pub async fn get_stale_listings(pool: &PgPool, days: Option<i32>) -> Result<Vec<StaleListing>> {
+    let mut conn = pool.acquire().await?;
+    
+    let settings = fetch_settings(&mut conn).await?;
+    let rows = fetch_listings(&mut conn, days.unwrap_or(settings.stale_after_days), false).await?;
+    
+    Ok(rows
+        .into_iter()
+        .map(|row| StaleListing {
+            next_price_drop: pricing::next_price_drop(&settings, &row.pc, row.last_drop),
+            pc: row.pc,
+        })
+        .collect())
+}
+
+/// Makes every scheduled price drop that has come due, at most one per PC, so
+/// it can run as often as you like. Nothing calls it on a timer; it runs when
+/// `POST /api/pcs/stale/price-drops` is called, e.g. from cron.
+pub async fn apply_price_drops(pool: &PgPool, actor: &Actor) -> Result<Vec<PcPriceChange>> {
+    let mut tx = pool.begin().await?;
+    
+    let settings = fetch_settings(&mut tx).await?;
+    let today = sqlx::query_scalar!(r#"SELECT CURRENT_DATE as "today!""#)
+        .fetch_one(&mut *tx)
+        .await?;
+    let rows = fetch_listings(&mut tx, settings.stale_after_days, true).await?;
+    
+    let mut changes = Vec::new();
+    for row in rows {
+        let Some(price_drop) = pricing::next_price_drop(&settings, &row.pc, row.last_drop) else {
+            continue;
+        };
+        if price_drop.due > today {
+            continue;
+        }
+        
+        let pc = sqlx::query_as!(
+            Pc,
+            r#"
+            UPDATE pcs SET intended_price = $2
+            WHERE id = $1
+            RETURNING
+                id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
+                buyer_id, platform, platform_reference, intended_price, actual_sale_price,
+                total_cost, profit, profit_percentage, notes, deleted_at,
+                days_on_market(pcs) as days_on_market,
+                status as "status!: PcStatus",
+                created_at as "created_at!",
+                updated_at as "updated_at!"
+            "#,
+            row.pc.id,
+            price_drop.price
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        record_event(&mut tx, actor, AuditEntity::Pc, pc.id, AuditAction::Update, Some(&row.pc), Some(&pc)).await?;
+        
+        let change = record_price_change(
+            &mut tx,
+            pc.id,
+            row.pc.intended_price,
+            pc.intended_price,
+            true,
+            actor,
+            Some(&format!("Scheduled drop after {} days listed", row.pc.days_on_market.unwrap_or_default())),
+        )
+        .await?;
+        changes.extend(change);
+    }
+    
+    tx.commit().await?;
+    
+    Ok(changes)
+}
+
+async fn fetch_settings(conn: &mut PgConnection) -> Result<ListingSettings> {
+    let settings = sqlx::query_as!(
+        ListingSettings,
+        r#"
+        SELECT
+            id, stale_after_days, price_drop_percent, price_drop_interval_days, min_margin,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM listing_settings
+        "#
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(settings)
+}
+
+async fn fetch_listings(conn: &mut PgConnection, days: i32, lock: bool) -> Result<Vec<ListingRow>> {
+    let mut sql = String::from(
+        r#"
+        SELECT
+            p.*,
+            days_on_market(p) as days_on_market,
+            (
+                SELECT MAX(changed_at)::date FROM pc_price_changes c
+                WHERE c.pc_id = p.id AND c.scheduled AND c.changed_at::date >= p.list_date
+            ) as last_drop
+        FROM pcs p
+        WHERE p.status = 'listed' AND p.deleted_at IS NULL AND p.list_date <= CURRENT_DATE - $1
+        ORDER BY p.list_date, p.pc_name
+        "#,
+    );
+    if lock {
+        sql.push_str(" FOR UPDATE OF p");
+    }
+    
+    let rows = sqlx::query_as::<_, ListingRow>(&sql).bind(days).fetch_all(&mut *conn).await?;
+    
+    Ok(rows)
+}
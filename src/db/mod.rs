@@ -3,8 +3,23 @@ pub mod pc_queries;
 pub mod inventory_queries;
 pub mod buyer_queries;
 pub mod report_queries;
+pub mod user_queries;
+pub mod audit_queries;
+pub mod import_queries;
+pub mod category_queries;
+pub mod listing_queries;
+pub mod purchasing_queries;
+pub mod serial_queries;
+mod filters;
 
 pub use pc_queries::*;
 pub use inventory_queries::*;
 pub use buyer_queries::*;
-pub use report_queries::*;
\ No newline at end of file
+pub use report_queries::*;
+pub use user_queries::*;
+pub use audit_queries::*;
+pub use import_queries::*;
+pub use category_queries::*;
+pub use listing_queries::*;
+pub use purchasing_queries::*;
+pub use serial_queries::*;
\ No newline at end of file
@@ -1,8 +1,116 @@
-use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use sqlx::{types::Json, PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 use anyhow::Result;
+use rust_decimal::Decimal;
+
+use crate::{compatibility, pricing};
+use crate::models::{Actor, AuditAction, AuditEntity, CompatibilityReport, ComponentType, IssueSeverity, Pc, PcPriceChange, PriceSuggestion, PcWithComponents, PcExport, CreatePcRequest, UpdatePcRequest, SellPcRequest, Component, ComponentSpecs, CreateComponentRequest, UpdateComponentRequest, PcStatus, PcStatusChange, StatusChangeRequest, StockMovement, StockMovementType, Page, PcListQuery, SortOrder, page_bounds};
+use super::audit_queries::record_event;
+use super::inventory_queries::{record_stock_movement, StockChange};
+use super::filters::{contains_pattern, push_order_and_page};
+use crate::validation::ValidationErrors;
+
+/// Reasons a component can't be added to a build
+#[derive(Debug)]
+pub enum ComponentError {
+    PartNotFound(Uuid),
+    InsufficientStock { part_id: Uuid, component_name: String, available: i32 },
+    MissingCost(String),
+}
+
+impl fmt::Display for ComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentError::PartNotFound(part_id) => write!(f, "Inventory part {} not found", part_id),
+            ComponentError::InsufficientStock { component_name, available, .. } => write!(
+                f,
+                "Not enough stock for {}: {} available",
+                component_name, available
+            ),
+            ComponentError::MissingCost(component_name) => write!(
+                f,
+                "No cost given for {} and no inventory buy-in price to copy",
+                component_name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComponentError {}
+
+/// A status change the PC state machine doesn't allow
+#[derive(Debug)]
+pub struct TransitionError {
+    pub from: PcStatus,
+    pub to: PcStatus,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot move PC from {} to {}", self.from, self.to)
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+/// A PC can't be listed while its parts don't fit together
+#[derive(Debug)]
+pub struct CompatibilityError(pub CompatibilityReport);
+
+impl fmt::Display for CompatibilityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let problems: Vec<&str> = self
+            .0
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .map(|issue| issue.message.as_str())
+            .collect();
+        write!(f, "PC can't be listed: {}", problems.join("; "))
+    }
+}
+
+impl std::error::Error for CompatibilityError {}
+
+/// Moving a PC out of a sale undoes it, which only someone who can set prices may do
+#[derive(Debug)]
+pub struct SaleLockedError;
+
+impl fmt::Display for SaleLockedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Moving a sold PC out of its sale needs permission to set prices")
+    }
+}
+
+impl std::error::Error for SaleLockedError {}
+
+/// A sold PC's parts left with the buyer, so its components are fixed
+#[derive(Debug)]
+pub struct PcSoldError;
+
+impl fmt::Display for PcSoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PC has been sold; its components can't be changed")
+    }
+}
+
+impl std::error::Error for PcSoldError {}
 
-use crate::models::{Pc, PcWithComponents, CreatePcRequest, UpdatePcRequest, SellPcRequest, Component, PcStatus};
+/// A sold PC can't be purged while serialized units are recorded in it, or
+/// they would lose track of who bought them
+#[derive(Debug)]
+pub struct SoldSerialsError(pub i64);
+
+impl fmt::Display for SoldSerialsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PC was sold with {} serialized unit(s) and can't be purged", self.0)
+    }
+}
+
+impl std::error::Error for SoldSerialsError {}
 
 pub async fn get_all_pcs(pool: &PgPool) -> Result<Vec<Pc>> {
     let pcs = sqlx::query_as!(
@@ -11,11 +119,13 @@ pub async fn get_all_pcs(pool: &PgPool) -> Result<Vec<Pc>> {
         SELECT 
             id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
             buyer_id, platform, platform_reference, intended_price, actual_sale_price,
-            total_cost, profit, profit_percentage, notes,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
             status as "status!: PcStatus", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
         FROM pcs 
+        WHERE deleted_at IS NULL
         ORDER BY created_at DESC
         "#
     )
@@ -25,6 +135,79 @@ pub async fn get_all_pcs(pool: &PgPool) -> Result<Vec<Pc>> {
     Ok(pcs)
 }
 
+pub async fn list_pcs(pool: &PgPool, query: &PcListQuery) -> Result<Page<Pc>> {
+    let (page, per_page) = page_bounds(query.page, query.per_page);
+    
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM pcs WHERE deleted_at IS NULL");
+    push_pc_filters(&mut count, query);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+    
+    let mut select = QueryBuilder::new(
+        r#"
+        SELECT 
+            id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
+            buyer_id, platform, platform_reference, intended_price, actual_sale_price,
+            total_cost, profit, profit_percentage, notes, deleted_at, status, created_at, updated_at,
+            days_on_market(pcs) as days_on_market
+        FROM pcs 
+        WHERE deleted_at IS NULL
+        "#
+    );
+    push_pc_filters(&mut select, query);
+    push_order_and_page(
+        &mut select,
+        &[query.sort.unwrap_or_default().column()],
+        query.order.unwrap_or(SortOrder::Desc),
+        page,
+        per_page,
+    );
+    let items = select.build_query_as::<Pc>().fetch_all(pool).await?;
+    
+    Ok(Page { items, total, page, per_page })
+}
+
+fn push_pc_filters(builder: &mut QueryBuilder<'_, Postgres>, query: &PcListQuery) {
+    if let Some(status) = query.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+    
+    let date_ranges = [
+        ("build_date", query.build_from, query.build_to),
+        ("list_date", query.list_from, query.list_to),
+        ("sale_date", query.sale_from, query.sale_to),
+    ];
+    for (column, from, to) in date_ranges {
+        if let Some(from) = from {
+            builder.push(format!(" AND {} >= ", column)).push_bind(from);
+        }
+        if let Some(to) = to {
+            builder.push(format!(" AND {} <= ", column)).push_bind(to);
+        }
+    }
+    
+    if let Some(platform) = &query.platform {
+        builder.push(" AND LOWER(platform) = LOWER(").push_bind(platform.clone()).push(")");
+    }
+    if let Some(buyer_id) = query.buyer_id {
+        builder.push(" AND buyer_id = ").push_bind(buyer_id);
+    }
+    if let Some(component_type) = &query.component_type {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM pc_components c WHERE c.pc_id = pcs.id AND c.component_type = ")
+            .push_bind(component_type.clone())
+            .push(")");
+    }
+    if let Some(min_price) = query.min_price {
+        builder.push(" AND COALESCE(actual_sale_price, intended_price) >= ").push_bind(min_price);
+    }
+    if let Some(max_price) = query.max_price {
+        builder.push(" AND COALESCE(actual_sale_price, intended_price) <= ").push_bind(max_price);
+    }
+    if let Some(q) = query.q.as_deref().filter(|q| !q.trim().is_empty()) {
+        builder.push(" AND pc_name ILIKE ").push_bind(contains_pattern(q.trim()));
+    }
+}
+
 pub async fn get_pc_with_components(pool: &PgPool, pc_id: Uuid) -> Result<Option<PcWithComponents>> {
     let pc = sqlx::query_as!(
         Pc,
@@ -32,12 +215,13 @@ pub async fn get_pc_with_components(pool: &PgPool, pc_id: Uuid) -> Result<Option
         SELECT 
             id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
             buyer_id, platform, platform_reference, intended_price, actual_sale_price,
-            total_cost, profit, profit_percentage, notes,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
             status as "status!: PcStatus", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
         FROM pcs 
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
         pc_id
     )
@@ -49,11 +233,12 @@ pub async fn get_pc_with_components(pool: &PgPool, pc_id: Uuid) -> Result<Option
             Component,
             r#"
             SELECT 
-                id, pc_id, component_name, cost, notes,
+                id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+                specs as "specs: Json<ComponentSpecs>",
                 component_type as "component_type: crate::models::ComponentType"
             FROM pc_components 
             WHERE pc_id = $1
-            ORDER BY component_type
+            ORDER BY (SELECT sort_order FROM component_categories WHERE slug = component_type)
             "#,
             pc_id
         )
@@ -66,23 +251,140 @@ pub async fn get_pc_with_components(pool: &PgPool, pc_id: Uuid) -> Result<Option
     }
 }
 
-pub async fn create_pc_with_components(pool: &PgPool, request: CreatePcRequest) -> Result<PcWithComponents> {
-    let mut tx = pool.begin().await?;
+/// Every live PC by name, with its components and buyer in one go rather than a query per PC
+pub async fn get_pc_exports(pool: &PgPool) -> Result<Vec<PcExport>> {
+    let pcs = sqlx::query_as!(
+        Pc,
+        r#"
+        SELECT 
+            id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
+            buyer_id, platform, platform_reference, intended_price, actual_sale_price,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
+            status as "status!: PcStatus", 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM pcs 
+        WHERE deleted_at IS NULL
+        ORDER BY pc_name
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
     
-    // Create the PC
-    let pc_id = Uuid::new_v4();
-    let pc = sqlx::query_as!(
+    let components = sqlx::query_as!(
+        Component,
+        r#"
+        SELECT 
+            c.id, c.pc_id, c.component_name, c.cost, c.notes, c.inventory_part_id, c.lot_id,
+            c.specs as "specs: Json<ComponentSpecs>",
+            c.component_type as "component_type: crate::models::ComponentType"
+        FROM pc_components c
+        JOIN pcs p ON p.id = c.pc_id
+        WHERE p.deleted_at IS NULL
+        ORDER BY (SELECT sort_order FROM component_categories WHERE slug = c.component_type), c.component_name, c.id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    // Sold PCs keep their buyer even if the buyer is in the trash
+    let buyers = sqlx::query!(
+        r#"
+        SELECT id, name, contact FROM buyers
+        WHERE id IN (SELECT buyer_id FROM pcs WHERE deleted_at IS NULL)
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    let buyers: HashMap<Uuid, (String, Option<String>)> = buyers
+        .into_iter()
+        .map(|buyer| (buyer.id, (buyer.name, buyer.contact)))
+        .collect();
+    
+    let mut components_by_pc: HashMap<Uuid, Vec<Component>> = HashMap::new();
+    for component in components {
+        components_by_pc.entry(component.pc_id).or_default().push(component);
+    }
+    
+    Ok(pcs
+        .into_iter()
+        .map(|pc| {
+            let (buyer_name, buyer_contact) = pc
+                .buyer_id
+                .and_then(|id| buyers.get(&id).cloned())
+                .map_or((None, None), |(name, contact)| (Some(name), contact));
+            PcExport {
+                buyer_name,
+                buyer_contact,
+                components: components_by_pc.remove(&pc.id).unwrap_or_default(),
+                pc,
+            }
+        })
+        .collect())
+}
+
+pub async fn get_price_suggestion(pool: &PgPool, pc_id: Uuid, target_margin: Option<Decimal>) -> Result<Option<PriceSuggestion>> {
+    let Some(build) = get_pc_with_components(pool, pc_id).await? else {
+        return Ok(None);
+    };
+    
+    let sold = sqlx::query_as!(
         Pc,
         r#"
-        INSERT INTO pcs (id, pc_name, build_date, intended_price, notes)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING 
+        SELECT 
             id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
             buyer_id, platform, platform_reference, intended_price, actual_sale_price,
-            total_cost, profit, profit_percentage, notes,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
             status as "status!: PcStatus", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
+        FROM pcs 
+        WHERE status = 'sold' AND deleted_at IS NULL
+            AND actual_sale_price IS NOT NULL AND total_cost > 0
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    let sold_ids: Vec<Uuid> = sold.iter().map(|pc| pc.id).collect();
+    let components = sqlx::query_as!(
+        Component,
+        r#"
+        SELECT 
+            id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+            specs as "specs: Json<ComponentSpecs>",
+            component_type as "component_type: crate::models::ComponentType"
+        FROM pc_components 
+        WHERE pc_id = ANY($1)
+        "#,
+        &sold_ids
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    let mut components_by_pc: HashMap<Uuid, Vec<Component>> = HashMap::new();
+    for component in components {
+        components_by_pc.entry(component.pc_id).or_default().push(component);
+    }
+    let sold: Vec<PcWithComponents> = sold
+        .into_iter()
+        .map(|pc| PcWithComponents { components: components_by_pc.remove(&pc.id).unwrap_or_default(), pc })
+        .collect();
+    
+    Ok(Some(pricing::suggest_price(&build, &sold, target_margin)?))
+}
+
+pub async fn create_pc_with_components(pool: &PgPool, request: CreatePcRequest, actor: &Actor) -> Result<PcWithComponents> {
+    let mut tx = pool.begin().await?;
+    
+    // Create the PC
+    let pc_id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO pcs (id, pc_name, build_date, intended_price, notes)
+        VALUES ($1, $2, $3, $4, $5)
         "#,
         pc_id,
         request.pc_name,
@@ -90,40 +392,60 @@ pub async fn create_pc_with_components(pool: &PgPool, request: CreatePcRequest)
         request.intended_price,
         request.notes
     )
-    .fetch_one(&mut *tx)
+    .execute(&mut *tx)
     .await?;
     
-    // Create components
+    record_status_change(&mut tx, pc_id, None, PcStatus::Building, actor, None).await?;
+    record_price_change(&mut tx, pc_id, None, request.intended_price, false, actor, None).await?;
+    
+    // Create components, taking linked parts out of stock
     let mut components = Vec::new();
     for comp_req in request.components {
-        let component = sqlx::query_as!(
-            Component,
-            r#"
-            INSERT INTO pc_components (id, pc_id, component_type, component_name, cost, notes)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING 
-                id, pc_id, component_name, cost, notes,
-                component_type as "component_type: crate::models::ComponentType"
-            "#,
-            Uuid::new_v4(),
-            pc_id,
-            comp_req.component_type as crate::models::ComponentType,
-            comp_req.component_name,
-            comp_req.cost,
-            comp_req.notes
-        )
-        .fetch_one(&mut *tx)
-        .await?;
-        
+        let component = insert_component(&mut tx, pc_id, comp_req, actor).await?;
         components.push(component);
     }
     
+    // Re-read the PC so totals reflect the component trigger
+    let pc = fetch_pc(&mut tx, pc_id).await?;
+    record_event(&mut tx, actor, AuditEntity::Pc, pc_id, AuditAction::Create, None, Some(&pc)).await?;
+    
     tx.commit().await?;
     
     Ok(PcWithComponents { pc, components })
 }
 
-pub async fn update_pc(pool: &PgPool, pc_id: Uuid, request: UpdatePcRequest) -> Result<Option<Pc>> {
+/// Edits a PC. `may_undo_sale` says whether the actor may move a sold PC to
+/// another status.
+pub async fn update_pc(
+    pool: &PgPool,
+    pc_id: Uuid,
+    request: UpdatePcRequest,
+    may_undo_sale: bool,
+    actor: &Actor,
+) -> Result<Option<Pc>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_pc(&mut tx, pc_id, false).await? else {
+        return Ok(None);
+    };
+    let current = before.status;
+    
+    // Dates left out of the request keep their stored values
+    let build_date = request.build_date.or(before.build_date);
+    let mut errors = ValidationErrors::default();
+    errors.date_order("list_date", build_date, request.list_date.or(before.list_date), "build_date");
+    errors.date_order("sale_date", build_date, before.sale_date, "build_date");
+    errors.into_result()?;
+    
+    if let Some(status) = request.status.filter(|status| *status != current) {
+        // Selling needs sale details, so it only goes through sell_pc
+        if status == PcStatus::Sold {
+            return Err(TransitionError { from: current, to: status }.into());
+        }
+        check_sale_lock(&before, status, may_undo_sale)?;
+        apply_transition(&mut tx, pc_id, current, status, actor, None).await?;
+    }
+    
     let pc = sqlx::query_as!(
         Pc,
         r#"
@@ -133,13 +455,13 @@ pub async fn update_pc(pool: &PgPool, pc_id: Uuid, request: UpdatePcRequest) ->
             list_date = COALESCE($4, list_date),
             intended_price = COALESCE($5, intended_price),
             notes = COALESCE($6, notes),
-            status = COALESCE($7, status),
             updated_at = NOW()
         WHERE id = $1
         RETURNING 
             id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
             buyer_id, platform, platform_reference, intended_price, actual_sale_price,
-            total_cost, profit, profit_percentage, notes,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
             status as "status!: PcStatus", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
@@ -149,16 +471,53 @@ pub async fn update_pc(pool: &PgPool, pc_id: Uuid, request: UpdatePcRequest) ->
         request.build_date,
         request.list_date,
         request.intended_price,
-        request.notes,
-        request.status as Option<PcStatus>
+        request.notes
     )
-    .fetch_optional(pool)
+    .fetch_one(&mut *tx)
     .await?;
     
-    Ok(pc)
+    record_price_change(
+        &mut tx,
+        pc_id,
+        before.intended_price,
+        pc.intended_price,
+        false,
+        actor,
+        request.price_change_reason.as_deref(),
+    )
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Pc, pc_id, AuditAction::Update, Some(&before), Some(&pc)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(pc))
 }
 
-pub async fn sell_pc(pool: &PgPool, pc_id: Uuid, request: SellPcRequest) -> Result<Option<Pc>> {
+pub async fn sell_pc(pool: &PgPool, pc_id: Uuid, request: SellPcRequest, actor: &Actor) -> Result<Option<Pc>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_pc(&mut tx, pc_id, false).await? else {
+        return Ok(None);
+    };
+    
+    let mut errors = ValidationErrors::default();
+    errors.date_order("sale_date", before.build_date, Some(request.sale_date), "build_date");
+    if let Some(buyer_id) = request.buyer_id {
+        let buyer_in_trash = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM buyers WHERE id = $1 AND deleted_at IS NOT NULL) as "exists!""#,
+            buyer_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if buyer_in_trash {
+            errors.add("buyer_id", "deleted", "This buyer is in the trash; restore them first");
+        }
+    }
+    errors.into_result()?;
+    
+    apply_transition(&mut tx, pc_id, before.status, PcStatus::Sold, actor, None).await?;
+    
     let pc = sqlx::query_as!(
         Pc,
         r#"
@@ -168,13 +527,13 @@ pub async fn sell_pc(pool: &PgPool, pc_id: Uuid, request: SellPcRequest) -> Resu
             buyer_id = $4,
             platform = $5,
             platform_reference = $6,
-            status = 'sold',
             updated_at = NOW()
         WHERE id = $1
         RETURNING 
             id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
             buyer_id, platform, platform_reference, intended_price, actual_sale_price,
-            total_cost, profit, profit_percentage, notes,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
             status as "status!: PcStatus", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
@@ -186,19 +545,715 @@ pub async fn sell_pc(pool: &PgPool, pc_id: Uuid, request: SellPcRequest) -> Resu
         request.platform,
         request.platform_reference
     )
-    .fetch_optional(pool)
+    .fetch_one(&mut *tx)
     .await?;
     
-    Ok(pc)
+    record_event(&mut tx, actor, AuditEntity::Pc, pc_id, AuditAction::Sell, Some(&before), Some(&pc)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(pc))
 }
 
-pub async fn delete_pc(pool: &PgPool, pc_id: Uuid) -> Result<bool> {
-    let result = sqlx::query!(
+/// Moves a PC to `status`. `may_undo_sale` says whether the actor may move a
+/// sold PC out of its sale.
+pub async fn change_pc_status(
+    pool: &PgPool,
+    pc_id: Uuid,
+    status: PcStatus,
+    request: StatusChangeRequest,
+    may_undo_sale: bool,
+    actor: &Actor,
+) -> Result<Option<Pc>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_pc(&mut tx, pc_id, false).await? else {
+        return Ok(None);
+    };
+    
+    check_sale_lock(&before, status, may_undo_sale)?;
+    apply_transition(&mut tx, pc_id, before.status, status, actor, request.reason.as_deref()).await?;
+    
+    let pc = fetch_pc(&mut tx, pc_id).await?;
+    record_event(&mut tx, actor, AuditEntity::Pc, pc_id, AuditAction::Update, Some(&before), Some(&pc)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(pc))
+}
+
+pub async fn get_pc_status_history(pool: &PgPool, pc_id: Uuid) -> Result<Option<Vec<PcStatusChange>>> {
+    if !pc_exists(pool, pc_id).await? {
+        return Ok(None);
+    }
+    
+    let history = sqlx::query_as!(
+        PcStatusChange,
+        r#"
+        SELECT 
+            id, pc_id,
+            from_status as "from_status: PcStatus",
+            to_status as "to_status: PcStatus",
+            changed_by, reason, changed_at
+        FROM pc_status_history 
+        WHERE pc_id = $1
+        ORDER BY changed_at, id
+        "#,
+        pc_id
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(Some(history))
+}
+
+pub async fn get_pc_price_history(pool: &PgPool, pc_id: Uuid) -> Result<Option<Vec<PcPriceChange>>> {
+    if !pc_exists(pool, pc_id).await? {
+        return Ok(None);
+    }
+    
+    let history = sqlx::query_as!(
+        PcPriceChange,
+        r#"
+        SELECT id, pc_id, old_price, new_price, scheduled, changed_by, reason, changed_at
+        FROM pc_price_changes
+        WHERE pc_id = $1
+        ORDER BY changed_at, id
+        "#,
+        pc_id
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(Some(history))
+}
+
+/// Moves a PC to the trash. Its components and the stock they took are kept so it can be restored.
+pub async fn delete_pc(pool: &PgPool, pc_id: Uuid, actor: &Actor) -> Result<bool> {
+    set_pc_deleted(pool, pc_id, true, actor).await.map(|pc| pc.is_some())
+}
+
+pub async fn get_deleted_pcs(pool: &PgPool) -> Result<Vec<Pc>> {
+    let pcs = sqlx::query_as!(
+        Pc,
+        r#"
+        SELECT 
+            id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
+            buyer_id, platform, platform_reference, intended_price, actual_sale_price,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
+            status as "status!: PcStatus", 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM pcs 
+        WHERE deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(pcs)
+}
+
+/// Takes a PC back out of the trash. Returns `None` if it isn't in the trash.
+pub async fn restore_pc(pool: &PgPool, pc_id: Uuid, actor: &Actor) -> Result<Option<Pc>> {
+    set_pc_deleted(pool, pc_id, false, actor).await
+}
+
+/// Permanently deletes a PC that is already in the trash, along with its components and history
+pub async fn purge_pc(pool: &PgPool, pc_id: Uuid, actor: &Actor) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(pc) = lock_pc(&mut tx, pc_id, true).await? else {
+        return Ok(false);
+    };
+    let components = fetch_components(&mut tx, pc_id).await?;
+    
+    if pc.sale_date.is_some() {
+        let serials = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM serialized_items s
+            JOIN pc_components c ON c.id = s.component_id
+            WHERE c.pc_id = $1
+            "#,
+            pc_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if serials > 0 {
+            return Err(SoldSerialsError(serials).into());
+        }
+    }
+    
+    // Parts of an unsold PC go back on the shelf; a sold PC's parts left with the buyer
+    if pc.status != PcStatus::Sold {
+        let mut used: BTreeMap<(Uuid, Option<Uuid>), i32> = BTreeMap::new();
+        for component in &components {
+            if let Some(part_id) = component.inventory_part_id {
+                *used.entry((part_id, component.lot_id)).or_default() += 1;
+            }
+        }
+        for ((part_id, lot_id), count) in used {
+            return_to_stock(&mut tx, part_id, lot_id, pc_id, count, actor).await?;
+        }
+    }
+    
+    sqlx::query!(
         "DELETE FROM pcs WHERE id = $1",
         pc_id
     )
-    .execute(pool)
+    .execute(&mut *tx)
+    .await?;
+    
+    let snapshot = PcWithComponents { pc, components };
+    record_event(&mut tx, actor, AuditEntity::Pc, pc_id, AuditAction::Purge, Some(&snapshot), None).await?;
+    
+    tx.commit().await?;
+    
+    Ok(true)
+}
+
+pub async fn get_pc_components(pool: &PgPool, pc_id: Uuid) -> Result<Option<Vec<Component>>> {
+    if !pc_exists(pool, pc_id).await? {
+        return Ok(None);
+    }
+    
+    let components = sqlx::query_as!(
+        Component,
+        r#"
+        SELECT 
+            id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+            specs as "specs: Json<ComponentSpecs>",
+            component_type as "component_type: crate::models::ComponentType"
+        FROM pc_components 
+        WHERE pc_id = $1
+        ORDER BY (SELECT sort_order FROM component_categories WHERE slug = component_type)
+        "#,
+        pc_id
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(Some(components))
+}
+
+pub async fn add_component(pool: &PgPool, pc_id: Uuid, request: CreateComponentRequest, actor: &Actor) -> Result<Option<Component>> {
+    let mut tx = pool.begin().await?;
+    
+    if !lock_unsold_pc(&mut tx, pc_id).await? {
+        return Ok(None);
+    }
+    
+    let component = insert_component(&mut tx, pc_id, request, actor).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(component))
+}
+
+pub async fn update_component(
+    pool: &PgPool,
+    pc_id: Uuid,
+    component_id: Uuid,
+    request: UpdateComponentRequest,
+    actor: &Actor,
+) -> Result<Option<Component>> {
+    let mut tx = pool.begin().await?;
+    
+    if !lock_unsold_pc(&mut tx, pc_id).await? {
+        return Ok(None);
+    }
+    
+    let before = sqlx::query_as!(
+        Component,
+        r#"
+        SELECT 
+            c.id, c.pc_id, c.component_name, c.cost, c.notes, c.inventory_part_id, c.lot_id,
+            c.specs as "specs: Json<ComponentSpecs>",
+            c.component_type as "component_type: crate::models::ComponentType"
+        FROM pc_components c
+        WHERE c.id = $1 AND c.pc_id = $2
+        FOR UPDATE
+        "#,
+        component_id,
+        pc_id
+    )
+    .fetch_optional(&mut *tx)
     .await?;
     
-    Ok(result.rows_affected() > 0)
-}
\ No newline at end of file
+    let Some(before) = before else {
+        return Ok(None);
+    };
+    
+    // Swapping parts returns the old one to stock and takes the new one
+    let mut cost = request.cost;
+    let mut lot_id = before.lot_id;
+    let swapped_part = request.inventory_part_id.filter(|part_id| Some(*part_id) != before.inventory_part_id);
+    if let Some(new_part_id) = swapped_part {
+        if let Some(old_part_id) = before.inventory_part_id {
+            return_to_stock(&mut tx, old_part_id, before.lot_id, pc_id, 1, actor).await?;
+        }
+        // A serialized unit of the old part doesn't come along
+        sqlx::query!("UPDATE serialized_items SET component_id = NULL WHERE component_id = $1", component_id)
+            .execute(&mut *tx)
+            .await?;
+        let component_name = request.component_name.as_deref().unwrap_or(&before.component_name);
+        let taken = take_from_stock(&mut tx, new_part_id, pc_id, component_name, actor).await?;
+        cost = cost.or(taken.unit_cost);
+        lot_id = taken.lot_id;
+    }
+    
+    let component = sqlx::query_as!(
+        Component,
+        r#"
+        UPDATE pc_components SET
+            component_type = COALESCE($3, component_type),
+            component_name = COALESCE($4, component_name),
+            cost = COALESCE($5, cost),
+            notes = COALESCE($6, notes),
+            inventory_part_id = COALESCE($7, inventory_part_id),
+            specs = specs || COALESCE($8, '{}'::jsonb),
+            lot_id = $9
+        WHERE id = $1 AND pc_id = $2
+        RETURNING 
+            id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+            specs as "specs: Json<ComponentSpecs>",
+            component_type as "component_type: crate::models::ComponentType"
+        "#,
+        component_id,
+        pc_id,
+        request.component_type as Option<crate::models::ComponentType>,
+        request.component_name,
+        cost,
+        request.notes,
+        swapped_part,
+        request.specs.as_ref().map(serde_json::to_value).transpose()?,
+        lot_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Component, component_id, AuditAction::Update, Some(&before), Some(&component)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(component))
+}
+
+pub async fn delete_component(pool: &PgPool, pc_id: Uuid, component_id: Uuid, actor: &Actor) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    
+    if !lock_unsold_pc(&mut tx, pc_id).await? {
+        return Ok(false);
+    }
+    
+    let deleted = sqlx::query_as!(
+        Component,
+        r#"
+        DELETE FROM pc_components
+        WHERE id = $1 AND pc_id = $2
+        RETURNING 
+            id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+            specs as "specs: Json<ComponentSpecs>",
+            component_type as "component_type: crate::models::ComponentType"
+        "#,
+        component_id,
+        pc_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    
+    let Some(deleted) = deleted else {
+        return Ok(false);
+    };
+    
+    if let Some(part_id) = deleted.inventory_part_id {
+        return_to_stock(&mut tx, part_id, deleted.lot_id, pc_id, 1, actor).await?;
+    }
+    
+    record_event(&mut tx, actor, AuditEntity::Component, component_id, AuditAction::Delete, Some(&deleted), None).await?;
+    
+    tx.commit().await?;
+    
+    Ok(true)
+}
+
+pub(crate) async fn insert_component(conn: &mut PgConnection, pc_id: Uuid, request: CreateComponentRequest, actor: &Actor) -> Result<Component> {
+    let mut cost = request.cost;
+    let mut lot_id = None;
+    if let Some(part_id) = request.inventory_part_id {
+        let taken = take_from_stock(conn, part_id, pc_id, &request.component_name, actor).await?;
+        cost = cost.or(taken.unit_cost);
+        lot_id = taken.lot_id;
+    }
+    let cost = cost.ok_or_else(|| ComponentError::MissingCost(request.component_name.clone()))?;
+    
+    let component = sqlx::query_as!(
+        Component,
+        r#"
+        INSERT INTO pc_components (id, pc_id, component_type, component_name, cost, notes, inventory_part_id, lot_id, specs)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING 
+            id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+            specs as "specs: Json<ComponentSpecs>",
+            component_type as "component_type: crate::models::ComponentType"
+        "#,
+        Uuid::new_v4(),
+        pc_id,
+        request.component_type as crate::models::ComponentType,
+        request.component_name,
+        cost,
+        request.notes,
+        request.inventory_part_id,
+        lot_id,
+        serde_json::to_value(&request.specs)?
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    record_event(conn, actor, AuditEntity::Component, component.id, AuditAction::Create, None, Some(&component)).await?;
+    
+    Ok(component)
+}
+
+async fn pc_exists<'e, E>(executor: E, pc_id: Uuid) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM pcs WHERE id = $1 AND deleted_at IS NULL) as "exists!""#,
+        pc_id
+    )
+    .fetch_one(executor)
+    .await?;
+    
+    Ok(exists)
+}
+
+/// Takes one unit of an inventory part. The movement has the lot it came
+/// from and its cost by the costing method.
+async fn take_from_stock(
+    conn: &mut PgConnection,
+    part_id: Uuid,
+    pc_id: Uuid,
+    component_name: &str,
+    actor: &Actor,
+) -> Result<StockMovement> {
+    let taken = sqlx::query!(
+        r#"
+        UPDATE parts_inventory SET
+            quantity_available = quantity_available - 1,
+            updated_at = NOW()
+        WHERE id = $1 AND deleted_at IS NULL AND quantity_available > 0
+        "#,
+        part_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    if taken.rows_affected() > 0 {
+        return record_stock_movement(conn, part_id, StockChange::build(StockMovementType::Consume, -1, pc_id), actor).await;
+    }
+    
+    let available = sqlx::query_scalar!(
+        "SELECT quantity_available FROM parts_inventory WHERE id = $1 AND deleted_at IS NULL",
+        part_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    match available {
+        Some(available) => Err(ComponentError::InsufficientStock {
+            part_id,
+            component_name: component_name.to_string(),
+            available,
+        }.into()),
+        None => Err(ComponentError::PartNotFound(part_id).into()),
+    }
+}
+
+/// Puts `count` parts from a build back on the shelf, in the lot they were taken from
+async fn return_to_stock(
+    conn: &mut PgConnection,
+    part_id: Uuid,
+    lot_id: Option<Uuid>,
+    pc_id: Uuid,
+    count: i32,
+    actor: &Actor,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE parts_inventory SET
+            quantity_available = quantity_available + $2,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        part_id,
+        count
+    )
+    .execute(&mut *conn)
+    .await?;
+    let change = StockChange { lot_id, ..StockChange::build(StockMovementType::Return, count, pc_id) };
+    record_stock_movement(conn, part_id, change, actor).await?;
+    
+    Ok(())
+}
+
+async fn set_pc_deleted(pool: &PgPool, pc_id: Uuid, deleted: bool, actor: &Actor) -> Result<Option<Pc>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_pc(&mut tx, pc_id, !deleted).await? else {
+        return Ok(None);
+    };
+    
+    sqlx::query!(
+        "UPDATE pcs SET deleted_at = CASE WHEN $2 THEN NOW() END WHERE id = $1",
+        pc_id,
+        deleted
+    )
+    .execute(&mut *tx)
+    .await?;
+    
+    let pc = fetch_pc(&mut tx, pc_id).await?;
+    let action = if deleted { AuditAction::Delete } else { AuditAction::Restore };
+    record_event(&mut tx, actor, AuditEntity::Pc, pc_id, action, Some(&before), Some(&pc)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(pc))
+}
+
+/// Locks a PC for the rest of the transaction and returns it as it was before the change.
+/// Only finds the PC while it is in the trash if `in_trash` is set, and only outside it otherwise.
+async fn lock_pc(conn: &mut PgConnection, pc_id: Uuid, in_trash: bool) -> Result<Option<Pc>> {
+    let pc = sqlx::query_as!(
+        Pc,
+        r#"
+        SELECT 
+            id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
+            buyer_id, platform, platform_reference, intended_price, actual_sale_price,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
+            status as "status!: PcStatus", 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM pcs 
+        WHERE id = $1 AND (deleted_at IS NOT NULL) = $2
+        FOR UPDATE
+        "#,
+        pc_id,
+        in_trash
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    Ok(pc)
+}
+
+/// Locks a PC outside the trash before its components change. Returns `false`
+/// if there is no such PC and fails if it has been sold.
+async fn lock_unsold_pc(conn: &mut PgConnection, pc_id: Uuid) -> Result<bool> {
+    let Some(pc) = lock_pc(conn, pc_id, false).await? else {
+        return Ok(false);
+    };
+    if pc.sale_date.is_some() {
+        return Err(PcSoldError.into());
+    }
+    
+    Ok(true)
+}
+
+pub(crate) async fn fetch_pc(conn: &mut PgConnection, pc_id: Uuid) -> Result<Pc> {
+    let pc = sqlx::query_as!(
+        Pc,
+        r#"
+        SELECT 
+            id, pc_name, build_date, list_date, sale_date, days_listed, days_held,
+            buyer_id, platform, platform_reference, intended_price, actual_sale_price,
+            total_cost, profit, profit_percentage, notes, deleted_at,
+            days_on_market(pcs) as days_on_market,
+            status as "status!: PcStatus", 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM pcs 
+        WHERE id = $1
+        "#,
+        pc_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(pc)
+}
+
+async fn fetch_components(conn: &mut PgConnection, pc_id: Uuid) -> Result<Vec<Component>> {
+    let components = sqlx::query_as!(
+        Component,
+        r#"
+        SELECT 
+            id, pc_id, component_name, cost, notes, inventory_part_id, lot_id,
+            specs as "specs: Json<ComponentSpecs>",
+            component_type as "component_type: crate::models::ComponentType"
+        FROM pc_components 
+        WHERE pc_id = $1
+        ORDER BY (SELECT sort_order FROM component_categories WHERE slug = component_type)
+        "#,
+        pc_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    
+    Ok(components)
+}
+
+pub async fn get_pc_compatibility(pool: &PgPool, pc_id: Uuid) -> Result<Option<CompatibilityReport>> {
+    if !pc_exists(pool, pc_id).await? {
+        return Ok(None);
+    }
+    
+    let mut conn = pool.acquire().await?;
+    let report = check_compatibility(&mut conn, pc_id).await?;
+    
+    Ok(Some(report))
+}
+
+async fn check_compatibility(conn: &mut PgConnection, pc_id: Uuid) -> Result<CompatibilityReport> {
+    let components = fetch_components(conn, pc_id).await?;
+    let limits = sqlx::query!(
+        r#"
+        SELECT slug as "slug: ComponentType", max_per_pc as "max_per_pc!"
+        FROM component_categories
+        WHERE max_per_pc IS NOT NULL
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|row| (row.slug, row.max_per_pc))
+    .collect();
+    
+    Ok(compatibility::check_build(pc_id, &components, &limits))
+}
+
+/// A PC with a sale keeps its status unless the actor may undo the sale
+fn check_sale_lock(pc: &Pc, to: PcStatus, may_undo_sale: bool) -> Result<()> {
+    if pc.status != to && pc.sale_date.is_some() && !may_undo_sale {
+        return Err(SaleLockedError.into());
+    }
+    
+    Ok(())
+}
+
+/// Moves a PC to a new status, fixing up the dates and sale details that go with it
+async fn apply_transition(
+    conn: &mut PgConnection,
+    pc_id: Uuid,
+    from: PcStatus,
+    to: PcStatus,
+    actor: &Actor,
+    reason: Option<&str>,
+) -> Result<()> {
+    if !from.can_transition_to(to) {
+        return Err(TransitionError { from, to }.into());
+    }
+    
+    if to == PcStatus::Listed {
+        let report = check_compatibility(conn, pc_id).await?;
+        if !report.compatible {
+            return Err(CompatibilityError(report).into());
+        }
+    }
+    
+    // A PC back on the market or the bench has no sale: relisting a sold PC
+    // means the sale was returned, and rebuilding an archived one starts over
+    let sale_returned = matches!(to, PcStatus::Listed | PcStatus::Building);
+    
+    sqlx::query!(
+        r#"
+        UPDATE pcs SET
+            status = $2,
+            list_date = CASE
+                WHEN $2 = 'listed'::pc_status THEN COALESCE(list_date, CURRENT_DATE)
+                WHEN $2 = 'building'::pc_status THEN NULL
+                ELSE list_date
+            END,
+            sale_date = CASE WHEN $3 THEN NULL ELSE sale_date END,
+            actual_sale_price = CASE WHEN $3 THEN NULL ELSE actual_sale_price END,
+            buyer_id = CASE WHEN $3 THEN NULL ELSE buyer_id END,
+            platform = CASE WHEN $3 THEN NULL ELSE platform END,
+            platform_reference = CASE WHEN $3 THEN NULL ELSE platform_reference END,
+            profit = CASE WHEN $3 THEN NULL ELSE profit END,
+            profit_percentage = CASE WHEN $3 THEN NULL ELSE profit_percentage END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        pc_id,
+        to as PcStatus,
+        sale_returned
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    record_status_change(conn, pc_id, Some(from), to, actor, reason).await
+}
+
+pub(crate) async fn record_status_change(
+    conn: &mut PgConnection,
+    pc_id: Uuid,
+    from: Option<PcStatus>,
+    to: PcStatus,
+    actor: &Actor,
+    reason: Option<&str>,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO pc_status_history (pc_id, from_status, to_status, changed_by, reason)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        pc_id,
+        from as Option<PcStatus>,
+        to as PcStatus,
+        actor.name,
+        reason
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    Ok(())
+}
+
+/// Records a change to a PC's intended price; nothing is recorded when it stayed the same
+pub(crate) async fn record_price_change(
+    conn: &mut PgConnection,
+    pc_id: Uuid,
+    old_price: Option<Decimal>,
+    new_price: Option<Decimal>,
+    scheduled: bool,
+    actor: &Actor,
+    reason: Option<&str>,
+) -> Result<Option<PcPriceChange>> {
+    if old_price == new_price {
+        return Ok(None);
+    }
+    
+    let change = sqlx::query_as!(
+        PcPriceChange,
+        r#"
+        INSERT INTO pc_price_changes (pc_id, old_price, new_price, scheduled, changed_by, reason)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, pc_id, old_price, new_price, scheduled, changed_by, reason, changed_at
+        "#,
+        pc_id,
+        old_price,
+        new_price,
+        scheduled,
+        actor.name,
+        reason
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(Some(change))
+}
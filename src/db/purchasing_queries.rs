@@ -0,0 +1,521 @@
+use std::fmt;
+
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use anyhow::Result;
+use rust_decimal::Decimal;
+
+use crate::models::{
+    Actor, AuditAction, AuditEntity, ComponentType, CreatePurchaseOrderRequest, CreateSupplierRequest, PurchaseOrder,
+    PurchaseOrderLine, PurchaseOrderListQuery, PurchaseOrderStatus, PurchaseOrderWithLines, ReceivePurchaseOrderRequest,
+    StockMovementType, Supplier, UpdatePurchaseOrderRequest, UpdateSupplierRequest,
+};
+use crate::purchasing;
+use crate::validation::ValidationErrors;
+use super::audit_queries::record_event;
+use super::inventory_queries::{receive_stock, StockChange};
+
+/// A received or cancelled purchase order can't be changed any more
+#[derive(Debug)]
+pub struct PurchaseOrderClosedError(pub PurchaseOrderStatus);
+
+impl fmt::Display for PurchaseOrderClosedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Purchase order is already {}", self.0.as_str().replace('_', " "))
+    }
+}
+
+impl std::error::Error for PurchaseOrderClosedError {}
+
+pub async fn get_suppliers(pool: &PgPool) -> Result<Vec<Supplier>> {
+    let suppliers = sqlx::query_as!(
+        Supplier,
+        r#"
+        SELECT
+            id, name, website, contact, email, phone, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM suppliers
+        ORDER BY name
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(suppliers)
+}
+
+pub async fn get_supplier(pool: &PgPool, supplier_id: Uuid) -> Result<Option<Supplier>> {
+    let supplier = sqlx::query_as!(
+        Supplier,
+        r#"
+        SELECT
+            id, name, website, contact, email, phone, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM suppliers
+        WHERE id = $1
+        "#,
+        supplier_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    
+    Ok(supplier)
+}
+
+pub async fn create_supplier(pool: &PgPool, request: CreateSupplierRequest, actor: &Actor) -> Result<Supplier> {
+    let mut tx = pool.begin().await?;
+    
+    let supplier = sqlx::query_as!(
+        Supplier,
+        r#"
+        INSERT INTO suppliers (id, name, website, contact, email, phone, notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING
+            id, name, website, contact, email, phone, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        Uuid::new_v4(),
+        request.name.trim(),
+        request.website,
+        request.contact,
+        request.email,
+        request.phone,
+        request.notes
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Supplier, supplier.id, AuditAction::Create, None, Some(&supplier)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(supplier)
+}
+
+pub async fn update_supplier(pool: &PgPool, supplier_id: Uuid, request: UpdateSupplierRequest, actor: &Actor) -> Result<Option<Supplier>> {
+    let mut tx = pool.begin().await?;
+    
+    let before = sqlx::query_as!(
+        Supplier,
+        r#"
+        SELECT
+            id, name, website, contact, email, phone, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM suppliers
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        supplier_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(before) = before else {
+        return Ok(None);
+    };
+    
+    let supplier = sqlx::query_as!(
+        Supplier,
+        r#"
+        UPDATE suppliers SET
+            name = COALESCE($2, name),
+            website = COALESCE($3, website),
+            contact = COALESCE($4, contact),
+            email = COALESCE($5, email),
+            phone = COALESCE($6, phone),
+            notes = COALESCE($7, notes)
+        WHERE id = $1
+        RETURNING
+            id, name, website, contact, email, phone, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        supplier_id,
+        request.name.as_deref().map(str::trim),
+        request.website,
+        request.contact,
+        request.email,
+        request.phone,
+        request.notes
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::Supplier, supplier_id, AuditAction::Update, Some(&before), Some(&supplier)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(supplier))
+}
+
+/// Newest orders first
+pub async fn list_purchase_orders(pool: &PgPool, query: &PurchaseOrderListQuery) -> Result<Vec<PurchaseOrder>> {
+    let orders = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        SELECT
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM purchase_orders
+        WHERE ($1::purchase_order_status IS NULL OR status = $1)
+            AND ($2::uuid IS NULL OR supplier_id = $2)
+            AND (
+                NOT $3
+                OR (status IN ('ordered', 'partially_received') AND expected_date < CURRENT_DATE)
+            )
+        ORDER BY order_date DESC, created_at DESC
+        "#,
+        query.status as Option<PurchaseOrderStatus>,
+        query.supplier_id,
+        query.overdue.unwrap_or(false)
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(orders)
+}
+
+pub async fn get_purchase_order(pool: &PgPool, order_id: Uuid) -> Result<Option<PurchaseOrderWithLines>> {
+    let mut conn = pool.acquire().await?;
+    
+    let order = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        SELECT
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM purchase_orders
+        WHERE id = $1
+        "#,
+        order_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(order) = order else {
+        return Ok(None);
+    };
+    
+    with_lines(&mut conn, order).await.map(Some)
+}
+
+pub async fn create_purchase_order(pool: &PgPool, request: CreatePurchaseOrderRequest, actor: &Actor) -> Result<PurchaseOrderWithLines> {
+    let mut tx = pool.begin().await?;
+    
+    let order = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        INSERT INTO purchase_orders (
+            id, supplier_id, order_reference, order_date, expected_date, shipping_cost, customs_cost, notes
+        )
+        VALUES ($1, $2, $3, COALESCE($4, CURRENT_DATE), $5, $6, $7, $8)
+        RETURNING
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        Uuid::new_v4(),
+        request.supplier_id,
+        request.order_reference,
+        request.order_date,
+        request.expected_date,
+        request.shipping_cost.unwrap_or_default(),
+        request.customs_cost.unwrap_or_default(),
+        request.notes
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    let lines: Vec<(i32, Decimal)> = request.lines.iter().map(|line| (line.quantity, line.unit_cost)).collect();
+    let landed_unit_costs = purchasing::landed_unit_costs(order.shipping_cost + order.customs_cost, &lines);
+    for (line, landed_unit_cost) in request.lines.iter().zip(landed_unit_costs) {
+        sqlx::query!(
+            r#"
+            INSERT INTO purchase_order_lines (purchase_order_id, part_id, quantity_ordered, unit_cost, landed_unit_cost)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            order.id,
+            line.part_id,
+            line.quantity,
+            line.unit_cost,
+            landed_unit_cost
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    
+    let order = with_lines(&mut tx, order).await?;
+    record_event(&mut tx, actor, AuditEntity::PurchaseOrder, order.order.id, AuditAction::Create, None, Some(&order)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(order)
+}
+
+/// Changes an open order and splits its shipping and customs over the lines again
+pub async fn update_purchase_order(
+    pool: &PgPool,
+    order_id: Uuid,
+    request: UpdatePurchaseOrderRequest,
+    actor: &Actor,
+) -> Result<Option<PurchaseOrderWithLines>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_order(&mut tx, order_id).await? else {
+        return Ok(None);
+    };
+    if !before.status.is_open() {
+        return Err(PurchaseOrderClosedError(before.status).into());
+    }
+    let mut errors = ValidationErrors::default();
+    errors.date_order("expected_date", Some(before.order_date), request.expected_date, "order_date");
+    errors.into_result()?;
+    
+    let order = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        UPDATE purchase_orders SET
+            order_reference = COALESCE($2, order_reference),
+            expected_date = COALESCE($3, expected_date),
+            shipping_cost = COALESCE($4, shipping_cost),
+            customs_cost = COALESCE($5, customs_cost),
+            notes = COALESCE($6, notes)
+        WHERE id = $1
+        RETURNING
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        order_id,
+        request.order_reference,
+        request.expected_date,
+        request.shipping_cost,
+        request.customs_cost,
+        request.notes
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    let lines = sqlx::query!(
+        "SELECT id, quantity_ordered, unit_cost FROM purchase_order_lines WHERE purchase_order_id = $1 ORDER BY id",
+        order_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let costs: Vec<(i32, Decimal)> = lines.iter().map(|line| (line.quantity_ordered, line.unit_cost)).collect();
+    let landed_unit_costs = purchasing::landed_unit_costs(order.shipping_cost + order.customs_cost, &costs);
+    for (line, landed_unit_cost) in lines.iter().zip(landed_unit_costs) {
+        sqlx::query!(
+            "UPDATE purchase_order_lines SET landed_unit_cost = $2 WHERE id = $1",
+            line.id,
+            landed_unit_cost
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    
+    record_event(&mut tx, actor, AuditEntity::PurchaseOrder, order_id, AuditAction::Update, Some(&before), Some(&order)).await?;
+    let order = with_lines(&mut tx, order).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(order))
+}
+
+/// Books parts that arrived into stock at their landed cost, as a `receive`
+/// movement per line. Fails as a whole if any line asks for more than is
+/// still outstanding.
+pub async fn receive_purchase_order(
+    pool: &PgPool,
+    order_id: Uuid,
+    request: ReceivePurchaseOrderRequest,
+    actor: &Actor,
+) -> Result<Option<PurchaseOrderWithLines>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_order(&mut tx, order_id).await? else {
+        return Ok(None);
+    };
+    if !before.status.is_open() {
+        return Err(PurchaseOrderClosedError(before.status).into());
+    }
+    
+    let reason = match &before.order_reference {
+        Some(order_reference) => format!("Received on purchase order {}", order_reference),
+        None => "Received on purchase order".to_string(),
+    };
+    for (index, receipt) in request.lines.iter().enumerate() {
+        let line = sqlx::query!(
+            r#"
+            SELECT part_id, quantity_ordered, quantity_received, landed_unit_cost
+            FROM purchase_order_lines
+            WHERE id = $1 AND purchase_order_id = $2
+            FOR UPDATE
+            "#,
+            receipt.line_id,
+            order_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(line) = line else {
+            return Err(ValidationErrors::single(
+                format!("lines[{}].line_id", index),
+                "invalid_reference",
+                "Line is not on this purchase order",
+            )
+            .into());
+        };
+        let outstanding = line.quantity_ordered - line.quantity_received;
+        if receipt.quantity > outstanding {
+            return Err(ValidationErrors::single(
+                format!("lines[{}].quantity", index),
+                "exceeds_outstanding",
+                format!("quantity must be at most the {} still outstanding", outstanding),
+            )
+            .into());
+        }
+        
+        sqlx::query!(
+            "UPDATE purchase_order_lines SET quantity_received = quantity_received + $2 WHERE id = $1",
+            receipt.line_id,
+            receipt.quantity
+        )
+        .execute(&mut *tx)
+        .await?;
+        let change = StockChange {
+            movement_type: StockMovementType::Receive,
+            quantity: receipt.quantity,
+            pc_id: None,
+            purchase_order_id: Some(order_id),
+            lot_id: None,
+            unit_cost: Some(line.landed_unit_cost),
+            reason: Some(&reason),
+            reference: before.order_reference.as_deref(),
+        };
+        receive_stock(&mut tx, line.part_id, change, actor).await?;
+    }
+    
+    let order = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        UPDATE purchase_orders SET
+            status = CASE
+                WHEN (SELECT bool_and(quantity_received = quantity_ordered) FROM purchase_order_lines WHERE purchase_order_id = $1)
+                    THEN 'received'::purchase_order_status
+                ELSE 'partially_received'::purchase_order_status
+            END
+        WHERE id = $1
+        RETURNING
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        order_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::PurchaseOrder, order_id, AuditAction::Update, Some(&before), Some(&order)).await?;
+    let order = with_lines(&mut tx, order).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(order))
+}
+
+/// Stops waiting for the rest of an open order; anything received stays in stock
+pub async fn cancel_purchase_order(pool: &PgPool, order_id: Uuid, actor: &Actor) -> Result<Option<PurchaseOrderWithLines>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_order(&mut tx, order_id).await? else {
+        return Ok(None);
+    };
+    if !before.status.is_open() {
+        return Err(PurchaseOrderClosedError(before.status).into());
+    }
+    
+    let order = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        UPDATE purchase_orders SET status = 'cancelled'
+        WHERE id = $1
+        RETURNING
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        order_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::PurchaseOrder, order_id, AuditAction::Update, Some(&before), Some(&order)).await?;
+    let order = with_lines(&mut tx, order).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(order))
+}
+
+async fn lock_order(conn: &mut PgConnection, order_id: Uuid) -> Result<Option<PurchaseOrder>> {
+    let order = sqlx::query_as!(
+        PurchaseOrder,
+        r#"
+        SELECT
+            id, supplier_id, order_reference, order_date, expected_date,
+            status as "status: PurchaseOrderStatus",
+            shipping_cost, customs_cost, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM purchase_orders
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        order_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    Ok(order)
+}
+
+async fn with_lines(conn: &mut PgConnection, order: PurchaseOrder) -> Result<PurchaseOrderWithLines> {
+    let lines = sqlx::query_as!(
+        PurchaseOrderLine,
+        r#"
+        SELECT
+            l.id, l.purchase_order_id, l.part_id,
+            p.component_type as "component_type: ComponentType",
+            p.component_name,
+            l.quantity_ordered, l.quantity_received, l.unit_cost, l.landed_unit_cost
+        FROM purchase_order_lines l
+        JOIN parts_inventory p ON p.id = l.part_id
+        WHERE l.purchase_order_id = $1
+        ORDER BY p.component_type, p.component_name, l.id
+        "#,
+        order.id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    
+    let goods: Decimal = lines.iter().map(|line| Decimal::from(line.quantity_ordered) * line.unit_cost).sum();
+    let total_cost = goods + order.shipping_cost + order.customs_cost;
+    
+    Ok(PurchaseOrderWithLines { order, lines, total_cost })
+}
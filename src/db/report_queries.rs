@@ -1,7 +1,13 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
 use anyhow::Result;
+use rust_decimal::Decimal;
 
-use crate::models::{MonthlySummary, ProfitAnalysis};
+use crate::models::{ComponentType, MonthlySummary, ProfitAnalysis, BuyerLifetimeValue, TopBuyer, TopBuyersQuery, DiscountReport, DiscountReportQuery, SaleDiscount, ReorderReport, ReorderReportQuery};
+use crate::reorder::{self, StockLevel, DEFAULT_CONSUMPTION_DAYS, DEFAULT_COVER_DAYS, DEFAULT_REORDER_POINT};
+
+/// Most buyers a top-buyers report returns
+const MAX_TOP_BUYERS: i64 = 100;
 
 pub async fn get_monthly_summary(pool: &PgPool) -> Result<Vec<MonthlySummary>> {
     let summary = sqlx::query_as!(
@@ -15,7 +21,7 @@ pub async fn get_monthly_summary(pool: &PgPool) -> Result<Vec<MonthlySummary>> {
             AVG(days_held::decimal) as average_days_held,
             AVG(profit_percentage) as average_profit_margin
         FROM pcs 
-        WHERE sale_date IS NOT NULL
+        WHERE sale_date IS NOT NULL AND deleted_at IS NULL
         GROUP BY TO_CHAR(sale_date, 'YYYY-MM')
         ORDER BY TO_CHAR(sale_date, 'YYYY-MM') DESC
         "#
@@ -31,12 +37,14 @@ pub async fn get_profit_analysis(pool: &PgPool) -> Result<Vec<ProfitAnalysis>> {
         ProfitAnalysis,
         r#"
         SELECT 
-            component_type::text as "component_type!",
-            AVG(cost) as avg_cost,
+            c.component_type::text as "component_type!",
+            AVG(c.cost) as avg_cost,
             COUNT(*) as "total_usage!",
-            AVG(cost) as avg_profit_contribution
-        FROM pc_components
-        GROUP BY component_type
+            AVG(c.cost) as avg_profit_contribution
+        FROM pc_components c
+        JOIN pcs ON pcs.id = c.pc_id
+        WHERE pcs.deleted_at IS NULL
+        GROUP BY c.component_type
         ORDER BY avg_cost DESC
         "#
     )
@@ -44,4 +52,191 @@ pub async fn get_profit_analysis(pool: &PgPool) -> Result<Vec<ProfitAnalysis>> {
     .await?;
     
     Ok(analysis)
-}
\ No newline at end of file
+}
+
+pub async fn get_buyer_lifetime_value(pool: &PgPool, buyer_id: Uuid) -> Result<Option<BuyerLifetimeValue>> {
+    let mut query = QueryBuilder::new("SELECT * FROM (");
+    push_buyer_lifetime_values(&mut query);
+    query.push(") lifetime WHERE buyer_id = ").push_bind(buyer_id);
+    
+    let lifetime = query.build_query_as::<BuyerLifetimeValue>().fetch_optional(pool).await?;
+    
+    Ok(lifetime)
+}
+
+/// Buyers with at least one purchase, best first; ties share a rank
+pub async fn get_top_buyers(pool: &PgPool, query: &TopBuyersQuery) -> Result<Vec<TopBuyer>> {
+    let column = query.rank_by.unwrap_or_default().column();
+    let limit = query.limit.unwrap_or(10).clamp(1, MAX_TOP_BUYERS);
+    
+    let mut select = QueryBuilder::new(format!("SELECT RANK() OVER (ORDER BY {} DESC) as rank, * FROM (", column));
+    push_buyer_lifetime_values(&mut select);
+    select
+        .push(format!(") lifetime WHERE purchases > 0 ORDER BY {} DESC, name, buyer_id LIMIT ", column))
+        .push_bind(limit);
+    
+    let buyers = select.build_query_as::<TopBuyer>().fetch_all(pool).await?;
+    
+    Ok(buyers)
+}
+
+/// Sold PCs with a price history, newest sale first, with what they were
+/// discounted from their original price
+pub async fn get_discount_report(pool: &PgPool, query: &DiscountReportQuery) -> Result<DiscountReport> {
+    let sales = sqlx::query_as!(
+        SaleDiscount,
+        r#"
+        WITH first_prices AS (
+            SELECT DISTINCT ON (pc_id) pc_id, new_price
+            FROM pc_price_changes
+            WHERE new_price IS NOT NULL
+            ORDER BY pc_id, changed_at, id
+        ),
+        changes AS (
+            SELECT pc_id, COUNT(*) FILTER (WHERE old_price IS NOT NULL) as price_changes
+            FROM pc_price_changes
+            GROUP BY pc_id
+        ),
+        sales AS (
+            SELECT
+                p.id, p.pc_name, p.sale_date, p.intended_price, p.actual_sale_price,
+                COALESCE(f.new_price, p.intended_price) as original_price,
+                COALESCE(c.price_changes, 0) as price_changes
+            FROM pcs p
+            LEFT JOIN first_prices f ON f.pc_id = p.id
+            LEFT JOIN changes c ON c.pc_id = p.id
+            WHERE p.status = 'sold' AND p.deleted_at IS NULL AND p.actual_sale_price IS NOT NULL
+                AND ($1::date IS NULL OR p.sale_date >= $1)
+                AND ($2::date IS NULL OR p.sale_date <= $2)
+        )
+        SELECT 
+            id as "pc_id!",
+            pc_name as "pc_name!",
+            sale_date,
+            original_price as "original_price!",
+            intended_price as final_asking_price,
+            actual_sale_price as "actual_sale_price!",
+            original_price - actual_sale_price as "discount!",
+            ROUND((original_price - actual_sale_price) / NULLIF(original_price, 0) * 100, 2) as discount_percentage,
+            price_changes as "price_changes!"
+        FROM sales
+        WHERE original_price IS NOT NULL
+        ORDER BY sale_date DESC, pc_name
+        "#,
+        query.from,
+        query.to
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    let total_original_price: Decimal = sales.iter().map(|sale| sale.original_price).sum();
+    let total_sale_price: Decimal = sales.iter().map(|sale| sale.actual_sale_price).sum();
+    let total_discount = total_original_price - total_sale_price;
+    let discount_percentage = (total_original_price > Decimal::ZERO)
+        .then(|| (total_discount / total_original_price * Decimal::ONE_HUNDRED).round_dp(2));
+    
+    Ok(DiscountReport {
+        sales,
+        total_original_price,
+        total_sale_price,
+        total_discount,
+        discount_percentage,
+    })
+}
+
+/// Parts to reorder, with consumption counted from the stock movements of the
+/// last `days`
+pub async fn get_reorder_report(pool: &PgPool, query: &ReorderReportQuery) -> Result<ReorderReport> {
+    let days = query.days.unwrap_or(DEFAULT_CONSUMPTION_DAYS);
+    let cover_days = query.cover_days.unwrap_or(DEFAULT_COVER_DAYS);
+    
+    let levels = sqlx::query_as!(
+        StockLevel,
+        r#"
+        SELECT
+            p.id as part_id,
+            p.component_type as "component_type: ComponentType",
+            p.component_name,
+            p.quantity_available as "quantity_available!",
+            COALESCE(p.reorder_point, c.reorder_point, $2) as "reorder_point!",
+            COALESCE(p.target_stock, c.target_stock) as target_stock,
+            COALESCE((
+                SELECT -SUM(m.quantity)
+                FROM stock_movements m
+                WHERE m.part_id = p.id
+                    AND m.movement_type IN ('consume', 'sell', 'return')
+                    AND m.created_at >= NOW() - make_interval(days => $1)
+            ), 0)::int as "consumed!",
+            COALESCE((
+                SELECT SUM(l.quantity_ordered - l.quantity_received)
+                FROM purchase_order_lines l
+                JOIN purchase_orders o ON o.id = l.purchase_order_id
+                WHERE l.part_id = p.id AND o.status IN ('ordered', 'partially_received')
+            ), 0)::int as "on_order!",
+            p.buy_in_price
+        FROM parts_inventory p
+        JOIN component_categories c ON c.slug = p.component_type
+        WHERE p.deleted_at IS NULL
+        "#,
+        days,
+        DEFAULT_REORDER_POINT
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    let mut parts: Vec<_> = levels
+        .into_iter()
+        .filter_map(|level| reorder::suggest_reorder(level, days, cover_days))
+        .collect();
+    parts.sort_by(|a, b| {
+        let days_left = |days: Option<Decimal>| days.unwrap_or(Decimal::MAX);
+        days_left(a.days_of_stock)
+            .cmp(&days_left(b.days_of_stock))
+            .then(a.quantity_available.cmp(&b.quantity_available))
+            .then_with(|| a.component_name.cmp(&b.component_name))
+    });
+    let total_estimated_cost = parts.iter().filter_map(|part| part.estimated_cost).sum();
+    
+    Ok(ReorderReport {
+        days,
+        cover_days,
+        parts,
+        total_estimated_cost,
+    })
+}
+
+/// One row per buyer outside the trash with their lifetime figures
+fn push_buyer_lifetime_values(builder: &mut QueryBuilder<'_, Postgres>) {
+    builder.push(
+        r#"
+        WITH sales AS (
+            SELECT buyer_id, sale_date, actual_sale_price, profit, platform
+            FROM pcs
+            WHERE buyer_id IS NOT NULL AND status = 'sold' AND deleted_at IS NULL
+        ),
+        platforms AS (
+            SELECT DISTINCT ON (buyer_id) buyer_id, platform
+            FROM sales
+            WHERE platform IS NOT NULL
+            GROUP BY buyer_id, platform
+            ORDER BY buyer_id, COUNT(*) DESC, MAX(sale_date) DESC
+        )
+        SELECT 
+            b.id as buyer_id,
+            b.name,
+            COUNT(s.buyer_id) as purchases,
+            COALESCE(SUM(s.actual_sale_price), 0) as total_spent,
+            COALESCE(SUM(s.profit), 0) as total_profit,
+            MIN(s.sale_date) as first_purchase,
+            MAX(s.sale_date) as last_purchase,
+            ROUND((MAX(s.sale_date) - MIN(s.sale_date))::numeric / NULLIF(COUNT(s.sale_date) - 1, 0), 1)
+                as average_days_between_purchases,
+            p.platform as preferred_platform
+        FROM buyers b
+        LEFT JOIN sales s ON s.buyer_id = b.id
+        LEFT JOIN platforms p ON p.buyer_id = b.id
+        WHERE b.deleted_at IS NULL
+        GROUP BY b.id, b.name, p.platform
+        "#
+    );
+}
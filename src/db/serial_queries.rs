@@ -0,0 +1,389 @@
+use std::fmt;
+
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::models::{
+    Actor, AssignSerializedItemRequest, AuditAction, AuditEntity, ComponentType, CreateSerializedItemRequest, ItemCondition,
+    ItemLocation, PcStatus, SerialLocation, SerializedItem, TestStatus, UpdateSerializedItemRequest,
+};
+use crate::validation::ValidationErrors;
+use super::audit_queries::record_event;
+use super::pc_queries::PcSoldError;
+
+/// A component is one physical part, so it holds at most one serialized unit
+#[derive(Debug)]
+pub struct ComponentTakenError;
+
+impl fmt::Display for ComponentTakenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Another unit is already in this component")
+    }
+}
+
+impl std::error::Error for ComponentTakenError {}
+
+/// A serialized unit with the part, build and buyer it belongs to
+#[derive(FromRow)]
+struct LocationRow {
+    #[sqlx(flatten)]
+    item: SerializedItem,
+    component_type: ComponentType,
+    component_name: String,
+    pc_id: Option<Uuid>,
+    pc_name: Option<String>,
+    pc_status: Option<PcStatus>,
+    sale_date: Option<NaiveDate>,
+    platform: Option<String>,
+    buyer_id: Option<Uuid>,
+    buyer_name: Option<String>,
+}
+
+/// A part's serialized units by serial number, including while the part is in
+/// the trash. Returns `None` if the part doesn't exist.
+pub async fn get_part_serials(pool: &PgPool, part_id: Uuid) -> Result<Option<Vec<SerializedItem>>> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM parts_inventory WHERE id = $1) as "exists!""#,
+        part_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+    
+    let items = sqlx::query_as!(
+        SerializedItem,
+        r#"
+        SELECT
+            id, part_id, serial_number,
+            condition as "condition: ItemCondition",
+            test_status as "test_status: TestStatus",
+            acquired_on, acquired_from, supplier_id, component_id, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM serialized_items
+        WHERE part_id = $1
+        ORDER BY serial_number
+        "#,
+        part_id
+    )
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(Some(items))
+}
+
+pub async fn get_serialized_item(pool: &PgPool, item_id: Uuid) -> Result<Option<SerializedItem>> {
+    let item = sqlx::query_as!(
+        SerializedItem,
+        r#"
+        SELECT
+            id, part_id, serial_number,
+            condition as "condition: ItemCondition",
+            test_status as "test_status: TestStatus",
+            acquired_on, acquired_from, supplier_id, component_id, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM serialized_items
+        WHERE id = $1
+        "#,
+        item_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    
+    Ok(item)
+}
+
+/// Records a unit of a part by its serial number. Returns `None` if the part
+/// doesn't exist or is in the trash.
+pub async fn create_serialized_item(
+    pool: &PgPool,
+    part_id: Uuid,
+    request: CreateSerializedItemRequest,
+    actor: &Actor,
+) -> Result<Option<SerializedItem>> {
+    let mut tx = pool.begin().await?;
+    
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM parts_inventory WHERE id = $1 AND deleted_at IS NULL) as "exists!""#,
+        part_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if !exists {
+        return Ok(None);
+    }
+    
+    let item = sqlx::query_as!(
+        SerializedItem,
+        r#"
+        INSERT INTO serialized_items (
+            id, part_id, serial_number, condition, test_status, acquired_on, acquired_from, supplier_id, notes
+        )
+        VALUES ($1, $2, $3, COALESCE($4, 'new'::item_condition), COALESCE($5, 'untested'::item_test_status), $6, $7, $8, $9)
+        RETURNING
+            id, part_id, serial_number,
+            condition as "condition: ItemCondition",
+            test_status as "test_status: TestStatus",
+            acquired_on, acquired_from, supplier_id, component_id, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        Uuid::new_v4(),
+        part_id,
+        request.serial_number.trim(),
+        request.condition as Option<ItemCondition>,
+        request.test_status as Option<TestStatus>,
+        request.acquired_on,
+        request.acquired_from,
+        request.supplier_id,
+        request.notes
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::SerializedItem, item.id, AuditAction::Create, None, Some(&item)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(item))
+}
+
+pub async fn update_serialized_item(
+    pool: &PgPool,
+    item_id: Uuid,
+    request: UpdateSerializedItemRequest,
+    actor: &Actor,
+) -> Result<Option<SerializedItem>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_item(&mut tx, item_id).await? else {
+        return Ok(None);
+    };
+    
+    let item = sqlx::query_as!(
+        SerializedItem,
+        r#"
+        UPDATE serialized_items SET
+            serial_number = COALESCE($2, serial_number),
+            condition = COALESCE($3, condition),
+            test_status = COALESCE($4, test_status),
+            acquired_on = COALESCE($5, acquired_on),
+            acquired_from = COALESCE($6, acquired_from),
+            supplier_id = COALESCE($7, supplier_id),
+            notes = COALESCE($8, notes)
+        WHERE id = $1
+        RETURNING
+            id, part_id, serial_number,
+            condition as "condition: ItemCondition",
+            test_status as "test_status: TestStatus",
+            acquired_on, acquired_from, supplier_id, component_id, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        item_id,
+        request.serial_number.as_deref().map(str::trim),
+        request.condition as Option<ItemCondition>,
+        request.test_status as Option<TestStatus>,
+        request.acquired_on,
+        request.acquired_from,
+        request.supplier_id,
+        request.notes
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::SerializedItem, item_id, AuditAction::Update, Some(&before), Some(&item)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(item))
+}
+
+/// Puts a unit in a build as a component made from its part, or back on the
+/// shelf. The component can't already hold another unit or be in a trashed PC,
+/// and a unit sold in a PC stays in it. Returns `None` if the unit doesn't exist.
+pub async fn assign_serialized_item(
+    pool: &PgPool,
+    item_id: Uuid,
+    request: AssignSerializedItemRequest,
+    actor: &Actor,
+) -> Result<Option<SerializedItem>> {
+    let mut tx = pool.begin().await?;
+    
+    let Some(before) = lock_item(&mut tx, item_id).await? else {
+        return Ok(None);
+    };
+    
+    if let Some(current) = before.component_id.filter(|current| Some(*current) != request.component_id) {
+        let sold = sqlx::query_scalar!(
+            r#"
+            SELECT pc.sale_date IS NOT NULL as "sold!"
+            FROM pc_components c
+            JOIN pcs pc ON pc.id = c.pc_id
+            WHERE c.id = $1
+            FOR UPDATE OF pc
+            "#,
+            current
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if sold {
+            return Err(PcSoldError.into());
+        }
+    }
+    
+    if let Some(component_id) = request.component_id {
+        let component = sqlx::query!(
+            r#"
+            SELECT c.inventory_part_id, pc.deleted_at IS NOT NULL as "in_trash!"
+            FROM pc_components c
+            JOIN pcs pc ON pc.id = c.pc_id
+            WHERE c.id = $1
+            "#,
+            component_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(component) = component else {
+            return Err(ValidationErrors::single(
+                "component_id",
+                "invalid_reference",
+                "Referenced component_id does not exist",
+            )
+            .into());
+        };
+        if component.in_trash {
+            return Err(ValidationErrors::single(
+                "component_id",
+                "pc_in_trash",
+                "The component's PC is in the trash",
+            )
+            .into());
+        }
+        if component.inventory_part_id != Some(before.part_id) {
+            return Err(ValidationErrors::single(
+                "component_id",
+                "part_mismatch",
+                "The component isn't built from this unit's part",
+            )
+            .into());
+        }
+        
+        let taken = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM serialized_items WHERE component_id = $1 AND id <> $2) as "exists!""#,
+            component_id,
+            item_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken {
+            return Err(ComponentTakenError.into());
+        }
+    }
+    
+    let item = sqlx::query_as!(
+        SerializedItem,
+        r#"
+        UPDATE serialized_items SET
+            component_id = $2
+        WHERE id = $1
+        RETURNING
+            id, part_id, serial_number,
+            condition as "condition: ItemCondition",
+            test_status as "test_status: TestStatus",
+            acquired_on, acquired_from, supplier_id, component_id, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        "#,
+        item_id,
+        request.component_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    record_event(&mut tx, actor, AuditEntity::SerializedItem, item_id, AuditAction::Update, Some(&before), Some(&item)).await?;
+    
+    tx.commit().await?;
+    
+    Ok(Some(item))
+}
+
+/// Every unit with this serial number, with where it is now and who bought it
+pub async fn lookup_serial(pool: &PgPool, serial: &str) -> Result<Vec<SerialLocation>> {
+    let rows = sqlx::query_as::<_, LocationRow>(
+        r#"
+        SELECT
+            s.*,
+            p.component_type,
+            p.component_name,
+            pc.id as pc_id,
+            pc.pc_name,
+            pc.status as pc_status,
+            pc.sale_date,
+            pc.platform,
+            b.id as buyer_id,
+            b.name as buyer_name
+        FROM serialized_items s
+        JOIN parts_inventory p ON p.id = s.part_id
+        LEFT JOIN pc_components c ON c.id = s.component_id
+        LEFT JOIN pcs pc ON pc.id = c.pc_id
+        LEFT JOIN buyers b ON b.id = pc.buyer_id
+        WHERE LOWER(s.serial_number) = LOWER($1)
+        ORDER BY p.component_type, p.component_name
+        "#,
+    )
+    .bind(serial.trim())
+    .fetch_all(pool)
+    .await?;
+    
+    let locations = rows
+        .into_iter()
+        .map(|row| SerialLocation {
+            location: match row.pc_status {
+                None => ItemLocation::InStock,
+                Some(_) if row.sale_date.is_some() => ItemLocation::Sold,
+                Some(_) => ItemLocation::InBuild,
+            },
+            item: row.item,
+            component_type: row.component_type,
+            component_name: row.component_name,
+            pc_id: row.pc_id,
+            pc_name: row.pc_name,
+            pc_status: row.pc_status,
+            sale_date: row.sale_date,
+            platform: row.platform,
+            buyer_id: row.buyer_id,
+            buyer_name: row.buyer_name,
+        })
+        .collect();
+    
+    Ok(locations)
+}
+
+async fn lock_item(conn: &mut PgConnection, item_id: Uuid) -> Result<Option<SerializedItem>> {
+    let item = sqlx::query_as!(
+        SerializedItem,
+        r#"
+        SELECT
+            id, part_id, serial_number,
+            condition as "condition: ItemCondition",
+            test_status as "test_status: TestStatus",
+            acquired_on, acquired_from, supplier_id, component_id, notes,
+            created_at as "created_at!",
+            updated_at as "updated_at!"
+        FROM serialized_items
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        item_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    Ok(item)
+}
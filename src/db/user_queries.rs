@@ -0,0 +1,215 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::models::{CreateUserRequest, UpdateUserRequest, User, UserRole};
+use crate::validation::ValidationErrors;
+
+/// A user together with the stored password hash, used only to check a login
+pub struct UserCredentials {
+    pub user: User,
+    pub password_hash: String,
+}
+
+pub async fn get_all_users(pool: &PgPool) -> Result<Vec<User>> {
+    let users = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, username, display_name, role as "role: UserRole", created_at, updated_at
+        FROM users
+        ORDER BY username
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(users)
+}
+
+pub async fn create_user(pool: &PgPool, request: &CreateUserRequest, password_hash: &str) -> Result<User> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO users (username, display_name, password_hash, role)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, username, display_name, role as "role: UserRole", created_at, updated_at
+        "#,
+        request.username.trim(),
+        request.display_name,
+        password_hash,
+        request.role.unwrap_or(UserRole::Viewer) as UserRole
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(user)
+}
+
+/// Creates the first account on a fresh install. Returns `None` once any user exists.
+pub async fn create_first_user(pool: &PgPool, request: &CreateUserRequest, password_hash: &str) -> Result<Option<User>> {
+    let mut tx = pool.begin().await?;
+
+    // Two concurrent setup calls must not both see an empty table
+    sqlx::query!("LOCK TABLE users IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+
+    let has_users = sqlx::query_scalar!(r#"SELECT EXISTS (SELECT 1 FROM users) as "exists!""#)
+        .fetch_one(&mut *tx)
+        .await?;
+    if has_users {
+        return Ok(None);
+    }
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO users (username, display_name, password_hash, role)
+        VALUES ($1, $2, $3, 'owner')
+        RETURNING id, username, display_name, role as "role: UserRole", created_at, updated_at
+        "#,
+        request.username.trim(),
+        request.display_name,
+        password_hash
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(user))
+}
+
+/// Changes a user's name or role, refusing to demote the last owner
+pub async fn update_user(pool: &PgPool, user_id: Uuid, request: UpdateUserRequest) -> Result<Option<User>> {
+    let mut tx = pool.begin().await?;
+
+    let owners: Vec<Uuid> = sqlx::query_scalar!("SELECT id FROM users WHERE role = 'owner' FOR UPDATE")
+        .fetch_all(&mut *tx)
+        .await?;
+    let demoting_last_owner = owners == [user_id]
+        && request.role.is_some_and(|role| role != UserRole::Owner);
+    if demoting_last_owner {
+        return Err(ValidationErrors::single("role", "last_owner", "The last owner cannot be given another role").into());
+    }
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        UPDATE users SET
+            display_name = COALESCE($2, display_name),
+            role = COALESCE($3, role)
+        WHERE id = $1
+        RETURNING id, username, display_name, role as "role: UserRole", created_at, updated_at
+        "#,
+        user_id,
+        request.display_name,
+        request.role as Option<UserRole>
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(user)
+}
+
+pub async fn get_user_credentials(pool: &PgPool, username: &str) -> Result<Option<UserCredentials>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id, username, display_name, role as "role: UserRole", password_hash, created_at, updated_at
+        FROM users
+        WHERE LOWER(username) = LOWER($1)
+        "#,
+        username.trim()
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| UserCredentials {
+        user: User {
+            id: row.id,
+            username: row.username,
+            display_name: row.display_name,
+            role: row.role,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        },
+        password_hash: row.password_hash,
+    }))
+}
+
+pub async fn get_password_hash(pool: &PgPool, user_id: Uuid) -> Result<Option<String>> {
+    let hash = sqlx::query_scalar!("SELECT password_hash FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(hash)
+}
+
+/// Stores the new hash and signs the user out everywhere except the current session
+pub async fn update_password(pool: &PgPool, user_id: Uuid, password_hash: &str, keep_token_hash: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "UPDATE users SET password_hash = $2 WHERE id = $1",
+        user_id,
+        password_hash
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM user_sessions WHERE user_id = $1 AND token_hash <> $2",
+        user_id,
+        keep_token_hash
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn create_session(pool: &PgPool, user_id: Uuid, token_hash: &str, expires_at: DateTime<Utc>) -> Result<()> {
+    // Logins are rare enough to sweep expired sessions here
+    sqlx::query!("DELETE FROM user_sessions WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+
+    sqlx::query!(
+        "INSERT INTO user_sessions (token_hash, user_id, expires_at) VALUES ($1, $2, $3)",
+        token_hash,
+        user_id,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Looks up the owner of an unexpired session
+pub async fn get_session_user(pool: &PgPool, token_hash: &str) -> Result<Option<User>> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT u.id, u.username, u.display_name, u.role as "role: UserRole", u.created_at, u.updated_at
+        FROM user_sessions s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.token_hash = $1 AND s.expires_at > NOW()
+        "#,
+        token_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(user)
+}
+
+pub async fn delete_session(pool: &PgPool, token_hash: &str) -> Result<bool> {
+    let result = sqlx::query!("DELETE FROM user_sessions WHERE token_hash = $1", token_hash)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
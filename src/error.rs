@@ -0,0 +1,297 @@
+use std::fmt;
+
+use actix_web::{error::{JsonPayloadError, QueryPayloadError}, http::StatusCode, HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+use sqlx::postgres::PgDatabaseError;
+
+use crate::db::{CompatibilityError, ComponentError, ComponentTakenError, InsufficientStockError, PcSoldError, PurchaseOrderClosedError, SaleLockedError, SoldSerialsError, TransitionError};
+use crate::validation::{FieldError, ValidationErrors};
+
+/// Error returned by every handler.
+///
+/// Serializes as `{"error": "...", "code": "...", "field": "..."}` where `code`
+/// is a stable machine-readable identifier and `field` names the offending
+/// request field when one can be determined. Validation failures list every
+/// problem under `fields` instead.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    field: Option<String>,
+    fields: Vec<FieldError>,
+    source: Option<anyhow::Error>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+    code: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[FieldError]>::is_empty")]
+    fields: &'a [FieldError],
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+            field: None,
+            fields: Vec::new(),
+            source: None,
+        }
+    }
+
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(entity: &str) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", format!("{} not found", entity))
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, code, message)
+    }
+
+    pub fn unprocessable(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, code, message)
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn fields(&self) -> &[FieldError] {
+        &self.fields
+    }
+
+    fn internal(error: anyhow::Error) -> Self {
+        ApiError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "internal_error",
+            message: error.to_string(),
+            field: None,
+            fields: Vec::new(),
+            source: Some(error),
+        }
+    }
+
+    fn from_database_error(error: &PgDatabaseError) -> Option<Self> {
+        let field = error
+            .column()
+            .map(str::to_string)
+            .or_else(|| constraint_field(error.table(), error.constraint()));
+
+        let api_error = match error.code() {
+            // unique_violation
+            "23505" => Self::conflict(
+                "duplicate_value",
+                match &field {
+                    Some(field) => format!("A record with this {} already exists", field),
+                    None => "A record with these values already exists".to_string(),
+                },
+            ),
+            // foreign_key_violation: either pointing at a missing row, or deleting a row still in use
+            "23503" if error.detail().is_some_and(|detail| detail.contains("still referenced")) => Self::conflict(
+                "still_referenced",
+                "Record is still referenced by other records",
+            ),
+            "23503" => Self::unprocessable(
+                "invalid_reference",
+                match &field {
+                    Some(field) => format!("Referenced {} does not exist", field),
+                    None => "Referenced record does not exist".to_string(),
+                },
+            ),
+            // not_null_violation
+            "23502" => Self::unprocessable("missing_value", "A required value is missing"),
+            // check_violation
+            "23514" => Self::unprocessable("constraint_violation", error.message()),
+            // invalid_text_representation, e.g. an unknown enum value
+            "22P02" => Self::bad_request("invalid_value", error.message()),
+            // string_data_right_truncation
+            "22001" => Self::unprocessable("value_too_long", error.message()),
+            // numeric_value_out_of_range
+            "22003" => Self::unprocessable("value_out_of_range", error.message()),
+            _ => return None,
+        };
+
+        Some(match field {
+            Some(field) => api_error.with_field(field),
+            None => api_error,
+        })
+    }
+}
+
+/// Derives the column from Postgres' default constraint names,
+/// e.g. `pcs_pc_name_key` or `pcs_buyer_id_fkey` on table `pcs`.
+fn constraint_field(table: Option<&str>, constraint: Option<&str>) -> Option<String> {
+    let constraint = constraint?;
+    let column = constraint
+        .strip_prefix(table?)?
+        .strip_prefix('_')?;
+
+    ["_key", "_fkey", "_check"]
+        .iter()
+        .find_map(|suffix| column.strip_suffix(suffix))
+        .map(str::to_string)
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let Some(source) = &self.source {
+            log::error!("{:#}", source);
+        }
+
+        HttpResponse::build(self.status).json(ErrorBody {
+            error: &self.message,
+            code: self.code,
+            field: self.field.as_deref(),
+            fields: &self.fields,
+        })
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError {
+            fields: errors.errors,
+            ..Self::unprocessable("validation_failed", "Validation failed")
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(validation_errors) = cause.downcast_ref::<ValidationErrors>() {
+                return ValidationErrors {
+                    errors: validation_errors.errors.clone(),
+                }
+                .into();
+            }
+
+            if let Some(component_error) = cause.downcast_ref::<ComponentError>() {
+                return match component_error {
+                    ComponentError::PartNotFound(_) => {
+                        Self::unprocessable("part_not_found", component_error.to_string()).with_field("inventory_part_id")
+                    }
+                    ComponentError::InsufficientStock { .. } => {
+                        Self::conflict("insufficient_stock", component_error.to_string()).with_field("inventory_part_id")
+                    }
+                    ComponentError::MissingCost(_) => {
+                        Self::unprocessable("missing_cost", component_error.to_string()).with_field("cost")
+                    }
+                };
+            }
+
+            if let Some(stock_error) = cause.downcast_ref::<InsufficientStockError>() {
+                return Self::conflict("insufficient_stock", stock_error.to_string()).with_field("quantity");
+            }
+
+            if let Some(closed_error) = cause.downcast_ref::<PurchaseOrderClosedError>() {
+                return Self::conflict("purchase_order_closed", closed_error.to_string()).with_field("status");
+            }
+
+            if let Some(transition_error) = cause.downcast_ref::<TransitionError>() {
+                return Self::conflict("invalid_status_transition", transition_error.to_string()).with_field("status");
+            }
+
+            if let Some(sold_error) = cause.downcast_ref::<PcSoldError>() {
+                return Self::conflict("pc_sold", sold_error.to_string());
+            }
+
+            if let Some(sale_error) = cause.downcast_ref::<SaleLockedError>() {
+                return Self::forbidden(sale_error.to_string());
+            }
+
+            if let Some(compatibility_error) = cause.downcast_ref::<CompatibilityError>() {
+                return Self::conflict("incompatible_build", compatibility_error.to_string()).with_field("status");
+            }
+
+            if let Some(taken_error) = cause.downcast_ref::<ComponentTakenError>() {
+                return Self::conflict("already_assigned", taken_error.to_string()).with_field("component_id");
+            }
+
+            if let Some(serials_error) = cause.downcast_ref::<SoldSerialsError>() {
+                return Self::conflict("sold_serials", serials_error.to_string());
+            }
+
+            if let Some(sqlx_error) = cause.downcast_ref::<sqlx::Error>() {
+                match sqlx_error {
+                    sqlx::Error::RowNotFound => return Self::not_found("Record"),
+                    sqlx::Error::Database(db_error) => {
+                        let mapped = db_error
+                            .try_downcast_ref::<PgDatabaseError>()
+                            .and_then(Self::from_database_error);
+                        if let Some(api_error) = mapped {
+                            return api_error;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Self::internal(error)
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        anyhow::Error::from(error).into()
+    }
+}
+
+/// Turns malformed JSON bodies, including unknown enum values, into `400 invalid_body` errors
+pub fn json_error_handler(error: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let api_error = match &error {
+        JsonPayloadError::ContentType => ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+            "Expected a JSON body",
+        ),
+        JsonPayloadError::Deserialize(serde_error) if serde_error.is_data() => {
+            ApiError::bad_request("invalid_body", serde_error.to_string())
+        }
+        _ => ApiError::bad_request("invalid_json", error.to_string()),
+    };
+
+    api_error.into()
+}
+
+/// Turns unparseable query strings, e.g. an unknown sort field, into `400 invalid_query` errors
+pub fn query_error_handler(error: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::bad_request("invalid_query", error.to_string()).into()
+}
@@ -0,0 +1,22 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::models::{AuditListQuery, Permission};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+
+pub async fn list_audit(
+    query: web::Query<AuditListQuery>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ViewAudit)?;
+    
+    let events = db::list_audit_events(&pool, &query)
+        .await
+        .context("Failed to fetch audit events")?;
+    
+    Ok(HttpResponse::Ok().json(events))
+}
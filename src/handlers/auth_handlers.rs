@@ -0,0 +1,88 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+use crate::models::{ChangePasswordRequest, CreateUserRequest, LoginRequest, LoginResponse};
+use crate::auth::{self, AuthUser, SESSION_LIFETIME_DAYS};
+use crate::db;
+use crate::error::ApiError;
+use crate::validation::{Validate, ValidationErrors};
+
+/// Creates the first account on a fresh install; refused once any user exists
+pub async fn setup(
+    request: web::Json<CreateUserRequest>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let password_hash = auth::hash_password(&request.password)?;
+    let user = db::create_first_user(&pool, &request, &password_hash)
+        .await
+        .context("Failed to create user")?
+        .ok_or_else(|| ApiError::conflict("setup_complete", "Setup has already been completed"))?;
+    
+    Ok(HttpResponse::Created().json(user))
+}
+
+pub async fn login(
+    request: web::Json<LoginRequest>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let request = request.into_inner();
+    
+    let credentials = db::get_user_credentials(&pool, &request.username)
+        .await
+        .context("Failed to fetch user")?
+        .filter(|credentials| auth::verify_password(&request.password, &credentials.password_hash))
+        .ok_or_else(|| ApiError::unauthorized("Invalid username or password"))?;
+    
+    let token = auth::generate_token();
+    let expires_at = Utc::now() + Duration::days(SESSION_LIFETIME_DAYS);
+    db::create_session(&pool, credentials.user.id, &auth::hash_token(&token), expires_at)
+        .await
+        .context("Failed to create session")?;
+    
+    Ok(HttpResponse::Ok().json(LoginResponse {
+        token,
+        expires_at,
+        user: credentials.user,
+    }))
+}
+
+pub async fn logout(user: AuthUser, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    db::delete_session(&pool, &user.token_hash)
+        .await
+        .context("Failed to end session")?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn me(user: AuthUser) -> Result<HttpResponse, ApiError> {
+    Ok(HttpResponse::Ok().json(user.user))
+}
+
+pub async fn change_password(
+    user: AuthUser,
+    request: web::Json<ChangePasswordRequest>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let current_hash = db::get_password_hash(&pool, user.user.id)
+        .await
+        .context("Failed to fetch user")?
+        .ok_or_else(|| ApiError::not_found("User"))?;
+    if !auth::verify_password(&request.current_password, &current_hash) {
+        return Err(ValidationErrors::single("current_password", "incorrect", "current_password is incorrect").into());
+    }
+    
+    let password_hash = auth::hash_password(&request.new_password)?;
+    db::update_password(&pool, user.user.id, &password_hash, &user.token_hash)
+        .await
+        .context("Failed to change password")?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,47 +1,186 @@
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::{Buyer, CreateBuyerRequest};
+use crate::models::{Buyer, CreateBuyerRequest, UpdateBuyerRequest, MergeBuyersRequest, BuyerListQuery, BuyerSortField, Permission};
+use crate::auth::AuthUser;
 use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
 
-pub async fn list_buyers(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    match db::get_all_buyers(&pool).await {
-        Ok(buyers) => Ok(HttpResponse::Ok().json(buyers)),
-        Err(e) => {
-            eprintln!("Error fetching buyers: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch buyers"
-            })))
-        }
+pub async fn list_buyers(
+    query: web::Query<BuyerListQuery>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let show_contacts = user.can(Permission::ViewBuyerContacts);
+    if !show_contacts && matches!(query.sort, Some(BuyerSortField::Email)) {
+        return Err(ApiError::forbidden("Sorting by email requires access to buyer contact details"));
+    }
+    
+    let mut buyers = db::list_buyers(&pool, &query, show_contacts)
+        .await
+        .context("Failed to fetch buyers")?;
+    if !show_contacts {
+        buyers.items = buyers.items.into_iter().map(Buyer::without_contact_details).collect();
     }
+    
+    Ok(HttpResponse::Ok().json(buyers))
 }
 
 pub async fn create_buyer(
     request: web::Json<CreateBuyerRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageBuyers)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let buyer = db::create_buyer(&pool, request, &user.actor())
+        .await
+        .context("Failed to create buyer")?;
+    
+    Ok(HttpResponse::Created().json(buyer))
+}
+
+pub async fn get_buyer(
+    path: web::Path<Uuid>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
-    match db::create_buyer(&pool, request.into_inner()).await {
-        Ok(buyer) => Ok(HttpResponse::Created().json(buyer)),
-        Err(e) => {
-            eprintln!("Error creating buyer: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to create buyer"
-            })))
-        }
+) -> Result<HttpResponse, ApiError> {
+    let buyer = db::get_buyer(&pool, path.into_inner())
+        .await
+        .context("Failed to fetch buyer")?
+        .ok_or_else(|| ApiError::not_found("Buyer"))?;
+    
+    if user.can(Permission::ViewBuyerContacts) {
+        Ok(HttpResponse::Ok().json(buyer))
+    } else {
+        Ok(HttpResponse::Ok().json(buyer.without_contact_details()))
     }
 }
 
-pub async fn buyer_purchases(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    let buyer_id = path.into_inner();
+pub async fn update_buyer(
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateBuyerRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageBuyers)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
     
-    match db::get_buyer_purchases(&pool, buyer_id).await {
-        Ok(purchases) => Ok(HttpResponse::Ok().json(purchases)),
-        Err(e) => {
-            eprintln!("Error fetching buyer purchases: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch buyer purchases"
-            })))
-        }
+    let buyer = db::update_buyer(&pool, path.into_inner(), request, &user.actor())
+        .await
+        .context("Failed to update buyer")?
+        .ok_or_else(|| ApiError::not_found("Buyer"))?;
+    
+    Ok(HttpResponse::Ok().json(buyer))
+}
+
+pub async fn merge_buyers(
+    path: web::Path<Uuid>,
+    request: web::Json<MergeBuyersRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageBuyers)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let result = db::merge_buyers(&pool, path.into_inner(), &request.duplicate_ids, &user.actor())
+        .await
+        .context("Failed to merge buyers")?
+        .ok_or_else(|| ApiError::not_found("Buyer"))?;
+    
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Matching on email and phone would reveal them, so this is limited to roles that manage buyers
+pub async fn duplicate_buyers(
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageBuyers)?;
+    
+    let groups = db::find_duplicate_buyers(&pool)
+        .await
+        .context("Failed to find duplicate buyers")?;
+    
+    Ok(HttpResponse::Ok().json(groups))
+}
+
+pub async fn delete_buyer(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageBuyers)?;
+    
+    let deleted = db::delete_buyer(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to delete buyer")?;
+    
+    if !deleted {
+        return Err(ApiError::not_found("Buyer"));
+    }
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn restore_buyer(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageBuyers)?;
+    
+    let buyer = db::restore_buyer(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to restore buyer")?
+        .ok_or_else(|| ApiError::not_found("Deleted buyer"))?;
+    
+    Ok(HttpResponse::Ok().json(buyer))
+}
+
+pub async fn purge_buyer(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::PurgeRecords)?;
+    
+    let purged = db::purge_buyer(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to purge buyer")?;
+    
+    if !purged {
+        return Err(ApiError::not_found("Deleted buyer"));
     }
-}
\ No newline at end of file
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn buyer_profile(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let profile = db::get_buyer_lifetime_value(&pool, path.into_inner())
+        .await
+        .context("Failed to fetch buyer profile")?
+        .ok_or_else(|| ApiError::not_found("Buyer"))?;
+    
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+pub async fn buyer_purchases(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let buyer_id = path.into_inner();
+    
+    let purchases = db::get_buyer_purchases(&pool, buyer_id)
+        .await
+        .context("Failed to fetch buyer purchases")?;
+    
+    Ok(HttpResponse::Ok().json(purchases))
+}
@@ -0,0 +1,53 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::models::{ComponentType, CreateComponentCategoryRequest, UpdateComponentCategoryRequest, Permission};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
+
+pub async fn list_categories(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let categories = db::get_component_categories(&pool)
+        .await
+        .context("Failed to fetch component categories")?;
+    
+    Ok(HttpResponse::Ok().json(categories))
+}
+
+pub async fn create_category(
+    request: web::Json<CreateComponentCategoryRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageCategories)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let category = db::create_component_category(&pool, request, &user.actor())
+        .await
+        .context("Failed to create component category")?;
+    
+    Ok(HttpResponse::Created().json(category))
+}
+
+pub async fn update_category(
+    path: web::Path<String>,
+    request: web::Json<UpdateComponentCategoryRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageCategories)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let category = db::update_component_category(&pool, &ComponentType::new(&path), request, &user.actor())
+        .await
+        .context("Failed to update component category")?
+        .ok_or_else(|| ApiError::not_found("Component category"))?;
+    
+    Ok(HttpResponse::Ok().json(category))
+}
@@ -0,0 +1,91 @@
+use actix_web::{http::header::ContentDisposition, web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::models::{Buyer, Permission};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+use crate::spreadsheet::{csv, xlsx};
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+pub async fn export_pcs(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let pcs = db::get_pc_exports(&pool)
+        .await
+        .context("Failed to fetch PCs")?;
+    let categories = db::get_component_categories(&pool)
+        .await
+        .context("Failed to fetch component categories")?;
+    let body = csv::pcs_csv(&pcs, &categories).context("Failed to write PCs CSV")?;
+    
+    Ok(csv_response("pcs.csv", body))
+}
+
+pub async fn export_parts(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let parts = db::get_all_parts(&pool)
+        .await
+        .context("Failed to fetch inventory")?;
+    let body = csv::records_csv(&parts).context("Failed to write inventory CSV")?;
+    
+    Ok(csv_response("inventory.csv", body))
+}
+
+pub async fn export_buyers(
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let mut buyers = db::get_all_buyers(&pool)
+        .await
+        .context("Failed to fetch buyers")?;
+    if !user.can(Permission::ViewBuyerContacts) {
+        buyers = buyers.into_iter().map(Buyer::without_contact_details).collect();
+    }
+    let body = csv::records_csv(&buyers).context("Failed to write buyers CSV")?;
+    
+    Ok(csv_response("buyers.csv", body))
+}
+
+pub async fn export_monthly_summary(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let summary = db::get_monthly_summary(&pool)
+        .await
+        .context("Failed to fetch monthly summary")?;
+    let body = csv::records_csv(&summary).context("Failed to write monthly summary CSV")?;
+    
+    Ok(csv_response("monthly-summary.csv", body))
+}
+
+/// The whole tracker as one workbook: MAIN TRACKER, PRICE GUIDE and MONTHLY SUMMARY
+pub async fn export_xlsx(
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let mut pcs = db::get_pc_exports(&pool)
+        .await
+        .context("Failed to fetch PCs")?;
+    if !user.can(Permission::ViewBuyerContacts) {
+        for pc in &mut pcs {
+            pc.buyer_contact = None;
+        }
+    }
+    let parts = db::get_all_parts(&pool)
+        .await
+        .context("Failed to fetch inventory")?;
+    let summary = db::get_monthly_summary(&pool)
+        .await
+        .context("Failed to fetch monthly summary")?;
+    let body = xlsx::tracker_workbook(&pcs, &parts, &summary).context("Failed to write tracker workbook")?;
+    
+    Ok(HttpResponse::Ok()
+        .content_type(XLSX_CONTENT_TYPE)
+        .insert_header(ContentDisposition::attachment("pc-sales-tracker.xlsx"))
+        .body(body))
+}
+
+/// Served as a download so browsers save it under `filename`
+fn csv_response(filename: &str, body: Vec<u8>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(ContentDisposition::attachment(filename))
+        .body(body)
+}
@@ -0,0 +1,48 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::models::{CsvEntity, ImportQuery, Permission};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+use crate::spreadsheet::{csv, xlsx};
+
+/// Largest file accepted by the import endpoints
+pub const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Takes the .xlsx file as the raw request body. Answers 200 with the report
+/// either way; `committed` says whether anything was written.
+pub async fn import_xlsx(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ImportData)?;
+    
+    let rows = xlsx::read_workbook(&body)?;
+    let report = db::import_rows(&pool, rows, query.dry_run, &user.actor())
+        .await
+        .context("Failed to import workbook")?;
+    
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Takes a CSV file laid out like the matching export as the raw request body
+pub async fn import_csv(
+    entity: web::Path<CsvEntity>,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ImportData)?;
+    
+    let rows = csv::read_csv(entity.into_inner(), &body)?;
+    let report = db::import_rows(&pool, rows, query.dry_run, &user.actor())
+        .await
+        .context("Failed to import CSV")?;
+    
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -1,86 +1,184 @@
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::{InventoryPart, CreateInventoryPartRequest, UpdateInventoryPartRequest};
+use crate::models::{CreateInventoryPartRequest, CreateStockMovementRequest, LowStockQuery, UpdateInventoryPartRequest, PartListQuery, Permission};
+use crate::auth::AuthUser;
 use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
 
-pub async fn list_parts(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    match db::get_all_parts(&pool).await {
-        Ok(parts) => Ok(HttpResponse::Ok().json(parts)),
-        Err(e) => {
-            eprintln!("Error fetching parts: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch parts"
-            })))
-        }
-    }
+pub async fn list_parts(
+    query: web::Query<PartListQuery>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let parts = db::list_parts(&pool, &query)
+        .await
+        .context("Failed to fetch parts")?;
+    
+    Ok(HttpResponse::Ok().json(parts))
 }
 
 pub async fn create_part(
     request: web::Json<CreateInventoryPartRequest>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
-    match db::create_part(&pool, request.into_inner()).await {
-        Ok(part) => Ok(HttpResponse::Created().json(part)),
-        Err(e) => {
-            eprintln!("Error creating part: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to create part"
-            })))
-        }
-    }
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let part = db::create_part(&pool, request, &user.actor())
+        .await
+        .context("Failed to create part")?;
+    
+    Ok(HttpResponse::Created().json(part))
 }
 
 pub async fn update_part(
     path: web::Path<Uuid>,
     request: web::Json<UpdateInventoryPartRequest>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
     let part_id = path.into_inner();
     
-    match db::update_part(&pool, part_id, request.into_inner()).await {
-        Ok(Some(part)) => Ok(HttpResponse::Ok().json(part)),
-        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Part not found"
-        }))),
-        Err(e) => {
-            eprintln!("Error updating part: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to update part"
-            })))
-        }
-    }
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let part = db::update_part(&pool, part_id, request, &user.actor())
+        .await
+        .context("Failed to update part")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Ok().json(part))
+}
+
+pub async fn price_history(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let part_id = path.into_inner();
+    
+    let history = db::get_part_price_history(&pool, part_id)
+        .await
+        .context("Failed to fetch price history")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Ok().json(history))
+}
+
+pub async fn stock_movements(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let part_id = path.into_inner();
+    
+    let movements = db::get_stock_movements(&pool, part_id)
+        .await
+        .context("Failed to fetch stock movements")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Ok().json(movements))
+}
+
+pub async fn lots(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let part_id = path.into_inner();
+    
+    let lots = db::get_part_lots(&pool, part_id)
+        .await
+        .context("Failed to fetch inventory lots")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Ok().json(lots))
+}
+
+pub async fn create_stock_movement(
+    path: web::Path<Uuid>,
+    request: web::Json<CreateStockMovementRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let part_id = path.into_inner();
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let movement = db::create_stock_movement(&pool, part_id, request, &user.actor())
+        .await
+        .context("Failed to record stock movement")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Created().json(movement))
+}
+
+pub async fn stock_discrepancies(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let discrepancies = db::get_stock_discrepancies(&pool)
+        .await
+        .context("Failed to reconcile stock")?;
+    
+    Ok(HttpResponse::Ok().json(discrepancies))
 }
 
 pub async fn delete_part(
     path: web::Path<Uuid>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
     let part_id = path.into_inner();
     
-    match db::delete_part(&pool, part_id).await {
-        Ok(true) => Ok(HttpResponse::NoContent().finish()),
-        Ok(false) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Part not found"
-        }))),
-        Err(e) => {
-            eprintln!("Error deleting part: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to delete part"
-            })))
-        }
+    let deleted = db::delete_part(&pool, part_id, &user.actor())
+        .await
+        .context("Failed to delete part")?;
+    
+    if !deleted {
+        return Err(ApiError::not_found("Part"));
     }
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn restore_part(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let part = db::restore_part(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to restore part")?
+        .ok_or_else(|| ApiError::not_found("Deleted part"))?;
+    
+    Ok(HttpResponse::Ok().json(part))
 }
 
-pub async fn low_stock(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    match db::get_low_stock_parts(&pool, 5).await {
-        Ok(parts) => Ok(HttpResponse::Ok().json(parts)),
-        Err(e) => {
-            eprintln!("Error fetching low stock parts: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch low stock parts"
-            })))
-        }
+pub async fn purge_part(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::PurgeRecords)?;
+    
+    let purged = db::purge_part(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to purge part")?;
+    
+    if !purged {
+        return Err(ApiError::not_found("Deleted part"));
     }
-}
\ No newline at end of file
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn low_stock(query: web::Query<LowStockQuery>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    
+    let parts = db::get_low_stock_parts(&pool, query.threshold)
+        .await
+        .context("Failed to fetch low stock parts")?;
+    
+    Ok(HttpResponse::Ok().json(parts))
+}
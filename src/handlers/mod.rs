@@ -1,4 +1,14 @@
 pub mod pc_handlers;
 pub mod inventory_handlers;
 pub mod buyer_handlers;
-pub mod report_handlers;
\ No newline at end of file
+pub mod report_handlers;
+pub mod auth_handlers;
+pub mod user_handlers;
+pub mod audit_handlers;
+pub mod trash_handlers;
+pub mod import_handlers;
+pub mod export_handlers;
+pub mod category_handlers;
+pub mod settings_handlers;
+pub mod purchasing_handlers;
+pub mod serial_handlers;
@@ -1,109 +1,338 @@
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::{Pc, CreatePcRequest, UpdatePcRequest, SellPcRequest, PcWithComponents, Component, PcStatus};
+use crate::models::{CreatePcRequest, UpdatePcRequest, SellPcRequest, CreateComponentRequest, UpdateComponentRequest, PcStatus, StatusChangeRequest, PcListQuery, PriceSuggestionQuery, StaleListingQuery, Permission};
+use crate::auth::AuthUser;
 use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
 
-pub async fn list_pcs(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    match db::get_all_pcs(&pool).await {
-        Ok(pcs) => Ok(HttpResponse::Ok().json(pcs)),
-        Err(e) => {
-            eprintln!("Error fetching PCs: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch PCs"
-            })))
-        }
-    }
+pub async fn list_pcs(
+    query: web::Query<PcListQuery>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let pcs = db::list_pcs(&pool, &query)
+        .await
+        .context("Failed to fetch PCs")?;
+    
+    Ok(HttpResponse::Ok().json(pcs))
+}
+
+pub async fn stale_listings(query: web::Query<StaleListingQuery>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    
+    let listings = db::get_stale_listings(&pool, query.days)
+        .await
+        .context("Failed to fetch stale listings")?;
+    
+    Ok(HttpResponse::Ok().json(listings))
 }
 
-pub async fn get_pc(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse> {
+pub async fn apply_price_drops(user: AuthUser, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::SetPrices)?;
+    
+    let changes = db::apply_price_drops(&pool, &user.actor())
+        .await
+        .context("Failed to apply price drops")?;
+    
+    Ok(HttpResponse::Ok().json(changes))
+}
+
+pub async fn get_pc(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
     let pc_id = path.into_inner();
     
-    match db::get_pc_with_components(&pool, pc_id).await {
-        Ok(Some(pc)) => Ok(HttpResponse::Ok().json(pc)),
-        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "PC not found"
-        }))),
-        Err(e) => {
-            eprintln!("Error fetching PC: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch PC"
-            })))
-        }
-    }
+    let pc = db::get_pc_with_components(&pool, pc_id)
+        .await
+        .context("Failed to fetch PC")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(pc))
 }
 
 pub async fn create_pc(
     request: web::Json<CreatePcRequest>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
-    match db::create_pc_with_components(&pool, request.into_inner()).await {
-        Ok(pc) => Ok(HttpResponse::Created().json(pc)),
-        Err(e) => {
-            eprintln!("Error creating PC: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to create PC"
-            })))
-        }
-    }
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let pc = db::create_pc_with_components(&pool, request, &user.actor())
+        .await
+        .context("Failed to create PC")?;
+    
+    Ok(HttpResponse::Created().json(pc))
 }
 
 pub async fn update_pc(
     path: web::Path<Uuid>,
     request: web::Json<UpdatePcRequest>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
     let pc_id = path.into_inner();
     
-    match db::update_pc(&pool, pc_id, request.into_inner()).await {
-        Ok(Some(pc)) => Ok(HttpResponse::Ok().json(pc)),
-        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "PC not found"
-        }))),
-        Err(e) => {
-            eprintln!("Error updating PC: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to update PC"
-            })))
-        }
+    let request = request.into_inner();
+    request.validate()?;
+    if request.intended_price.is_some() {
+        user.require(Permission::SetPrices)?;
     }
+    
+    let pc = db::update_pc(&pool, pc_id, request, user.can(Permission::SetPrices), &user.actor())
+        .await
+        .context("Failed to update PC")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(pc))
 }
 
-pub async fn delete_pc(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse> {
+pub async fn delete_pc(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::DeletePcs)?;
+    
     let pc_id = path.into_inner();
     
-    match db::delete_pc(&pool, pc_id).await {
-        Ok(true) => Ok(HttpResponse::NoContent().finish()),
-        Ok(false) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "PC not found"
-        }))),
-        Err(e) => {
-            eprintln!("Error deleting PC: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to delete PC"
-            })))
-        }
+    let deleted = db::delete_pc(&pool, pc_id, &user.actor())
+        .await
+        .context("Failed to delete PC")?;
+    
+    if !deleted {
+        return Err(ApiError::not_found("PC"));
+    }
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn restore_pc(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::DeletePcs)?;
+    
+    let pc = db::restore_pc(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to restore PC")?
+        .ok_or_else(|| ApiError::not_found("Deleted PC"))?;
+    
+    Ok(HttpResponse::Ok().json(pc))
+}
+
+pub async fn purge_pc(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::PurgeRecords)?;
+    
+    let purged = db::purge_pc(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to purge PC")?;
+    
+    if !purged {
+        return Err(ApiError::not_found("Deleted PC"));
     }
+    
+    Ok(HttpResponse::NoContent().finish())
 }
 
 pub async fn sell_pc(
     path: web::Path<Uuid>,
     request: web::Json<SellPcRequest>,
+    user: AuthUser,
     pool: web::Data<PgPool>
-) -> Result<HttpResponse> {
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::SetPrices)?;
+    
     let pc_id = path.into_inner();
     
-    match db::sell_pc(&pool, pc_id, request.into_inner()).await {
-        Ok(Some(pc)) => Ok(HttpResponse::Ok().json(pc)),
-        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "PC not found"
-        }))),
-        Err(e) => {
-            eprintln!("Error selling PC: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to sell PC"
-            })))
-        }
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let pc = db::sell_pc(&pool, pc_id, request, &user.actor())
+        .await
+        .context("Failed to sell PC")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(pc))
+}
+
+pub async fn list_pc(
+    path: web::Path<Uuid>,
+    request: Option<web::Json<StatusChangeRequest>>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    change_status(path.into_inner(), PcStatus::Listed, request, &user, &pool).await
+}
+
+pub async fn unlist_pc(
+    path: web::Path<Uuid>,
+    request: Option<web::Json<StatusChangeRequest>>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    change_status(path.into_inner(), PcStatus::Building, request, &user, &pool).await
+}
+
+pub async fn archive_pc(
+    path: web::Path<Uuid>,
+    request: Option<web::Json<StatusChangeRequest>>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    change_status(path.into_inner(), PcStatus::Archived, request, &user, &pool).await
+}
+
+pub async fn status_history(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let pc_id = path.into_inner();
+    
+    let history = db::get_pc_status_history(&pool, pc_id)
+        .await
+        .context("Failed to fetch status history")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(history))
+}
+
+pub async fn price_history(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let pc_id = path.into_inner();
+    
+    let history = db::get_pc_price_history(&pool, pc_id)
+        .await
+        .context("Failed to fetch price history")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(history))
+}
+
+async fn change_status(
+    pc_id: Uuid,
+    status: PcStatus,
+    request: Option<web::Json<StatusChangeRequest>>,
+    user: &AuthUser,
+    pool: &PgPool
+) -> Result<HttpResponse, ApiError> {
+    let request = request.map(web::Json::into_inner).unwrap_or_default();
+    
+    let pc = db::change_pc_status(pool, pc_id, status, request, user.can(Permission::SetPrices), &user.actor())
+        .await
+        .context("Failed to change PC status")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(pc))
+}
+
+pub async fn list_components(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let pc_id = path.into_inner();
+    
+    let components = db::get_pc_components(&pool, pc_id)
+        .await
+        .context("Failed to fetch components")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(components))
+}
+
+pub async fn compatibility(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let pc_id = path.into_inner();
+    
+    let report = db::get_pc_compatibility(&pool, pc_id)
+        .await
+        .context("Failed to check compatibility")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub async fn price_suggestion(
+    path: web::Path<Uuid>,
+    query: web::Query<PriceSuggestionQuery>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    
+    let suggestion = db::get_price_suggestion(&pool, path.into_inner(), query.target_margin)
+        .await
+        .context("Failed to suggest a price")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Ok().json(suggestion))
+}
+
+pub async fn add_component(
+    path: web::Path<Uuid>,
+    request: web::Json<CreateComponentRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    let pc_id = path.into_inner();
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let component = db::add_component(&pool, pc_id, request, &user.actor())
+        .await
+        .context("Failed to add component")?
+        .ok_or_else(|| ApiError::not_found("PC"))?;
+    
+    Ok(HttpResponse::Created().json(component))
+}
+
+pub async fn update_component(
+    path: web::Path<(Uuid, Uuid)>,
+    request: web::Json<UpdateComponentRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    let (pc_id, component_id) = path.into_inner();
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let component = db::update_component(&pool, pc_id, component_id, request, &user.actor())
+        .await
+        .context("Failed to update component")?
+        .ok_or_else(|| ApiError::not_found("Component"))?;
+    
+    Ok(HttpResponse::Ok().json(component))
+}
+
+pub async fn delete_component(
+    path: web::Path<(Uuid, Uuid)>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    let (pc_id, component_id) = path.into_inner();
+    
+    let deleted = db::delete_component(&pool, pc_id, component_id, &user.actor())
+        .await
+        .context("Failed to delete component")?;
+    
+    if !deleted {
+        return Err(ApiError::not_found("Component"));
     }
-}
\ No newline at end of file
+    
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,156 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    CreatePurchaseOrderRequest, CreateSupplierRequest, Permission, PurchaseOrderListQuery, ReceivePurchaseOrderRequest,
+    UpdatePurchaseOrderRequest, UpdateSupplierRequest,
+};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
+
+pub async fn list_suppliers(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let suppliers = db::get_suppliers(&pool)
+        .await
+        .context("Failed to fetch suppliers")?;
+    
+    Ok(HttpResponse::Ok().json(suppliers))
+}
+
+pub async fn get_supplier(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let supplier = db::get_supplier(&pool, path.into_inner())
+        .await
+        .context("Failed to fetch supplier")?
+        .ok_or_else(|| ApiError::not_found("Supplier"))?;
+    
+    Ok(HttpResponse::Ok().json(supplier))
+}
+
+pub async fn create_supplier(
+    request: web::Json<CreateSupplierRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let supplier = db::create_supplier(&pool, request, &user.actor())
+        .await
+        .context("Failed to create supplier")?;
+    
+    Ok(HttpResponse::Created().json(supplier))
+}
+
+pub async fn update_supplier(
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateSupplierRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let supplier = db::update_supplier(&pool, path.into_inner(), request, &user.actor())
+        .await
+        .context("Failed to update supplier")?
+        .ok_or_else(|| ApiError::not_found("Supplier"))?;
+    
+    Ok(HttpResponse::Ok().json(supplier))
+}
+
+pub async fn list_purchase_orders(
+    query: web::Query<PurchaseOrderListQuery>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let orders = db::list_purchase_orders(&pool, &query)
+        .await
+        .context("Failed to fetch purchase orders")?;
+    
+    Ok(HttpResponse::Ok().json(orders))
+}
+
+pub async fn get_purchase_order(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let order = db::get_purchase_order(&pool, path.into_inner())
+        .await
+        .context("Failed to fetch purchase order")?
+        .ok_or_else(|| ApiError::not_found("Purchase order"))?;
+    
+    Ok(HttpResponse::Ok().json(order))
+}
+
+pub async fn create_purchase_order(
+    request: web::Json<CreatePurchaseOrderRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let order = db::create_purchase_order(&pool, request, &user.actor())
+        .await
+        .context("Failed to create purchase order")?;
+    
+    Ok(HttpResponse::Created().json(order))
+}
+
+pub async fn update_purchase_order(
+    path: web::Path<Uuid>,
+    request: web::Json<UpdatePurchaseOrderRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let order = db::update_purchase_order(&pool, path.into_inner(), request, &user.actor())
+        .await
+        .context("Failed to update purchase order")?
+        .ok_or_else(|| ApiError::not_found("Purchase order"))?;
+    
+    Ok(HttpResponse::Ok().json(order))
+}
+
+pub async fn receive_purchase_order(
+    path: web::Path<Uuid>,
+    request: web::Json<ReceivePurchaseOrderRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let order = db::receive_purchase_order(&pool, path.into_inner(), request, &user.actor())
+        .await
+        .context("Failed to receive purchase order")?
+        .ok_or_else(|| ApiError::not_found("Purchase order"))?;
+    
+    Ok(HttpResponse::Ok().json(order))
+}
+
+pub async fn cancel_purchase_order(
+    path: web::Path<Uuid>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let order = db::cancel_purchase_order(&pool, path.into_inner(), &user.actor())
+        .await
+        .context("Failed to cancel purchase order")?
+        .ok_or_else(|| ApiError::not_found("Purchase order"))?;
+    
+    Ok(HttpResponse::Ok().json(order))
+}
@@ -1,28 +1,52 @@
-use actix_web::{web, HttpResponse, Result};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
 use sqlx::PgPool;
 
+use crate::models::{DiscountReportQuery, ReorderReportQuery, TopBuyersQuery};
 use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
 
-pub async fn monthly_summary(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    match db::get_monthly_summary(&pool).await {
-        Ok(summary) => Ok(HttpResponse::Ok().json(summary)),
-        Err(e) => {
-            eprintln!("Error fetching monthly summary: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch monthly summary"
-            })))
-        }
-    }
+pub async fn monthly_summary(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let summary = db::get_monthly_summary(&pool)
+        .await
+        .context("Failed to fetch monthly summary")?;
+    
+    Ok(HttpResponse::Ok().json(summary))
 }
 
-pub async fn profit_analysis(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    match db::get_profit_analysis(&pool).await {
-        Ok(analysis) => Ok(HttpResponse::Ok().json(analysis)),
-        Err(e) => {
-            eprintln!("Error fetching profit analysis: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch profit analysis"
-            })))
-        }
-    }
-}
\ No newline at end of file
+pub async fn profit_analysis(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let analysis = db::get_profit_analysis(&pool)
+        .await
+        .context("Failed to fetch profit analysis")?;
+    
+    Ok(HttpResponse::Ok().json(analysis))
+}
+
+pub async fn top_buyers(query: web::Query<TopBuyersQuery>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let buyers = db::get_top_buyers(&pool, &query)
+        .await
+        .context("Failed to fetch top buyers")?;
+    
+    Ok(HttpResponse::Ok().json(buyers))
+}
+
+pub async fn discounts(query: web::Query<DiscountReportQuery>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    
+    let report = db::get_discount_report(&pool, &query)
+        .await
+        .context("Failed to fetch discount report")?;
+    
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub async fn reorder(query: web::Query<ReorderReportQuery>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    
+    let report = db::get_reorder_report(&pool, &query)
+        .await
+        .context("Failed to fetch reorder report")?;
+    
+    Ok(HttpResponse::Ok().json(report))
+}
@@ -0,0 +1,97 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    AssignSerializedItemRequest, CreateSerializedItemRequest, Permission, SerialLookupQuery, UpdateSerializedItemRequest,
+};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
+
+pub async fn list_part_serials(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let items = db::get_part_serials(&pool, path.into_inner())
+        .await
+        .context("Failed to fetch serialized items")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Ok().json(items))
+}
+
+pub async fn create_serialized_item(
+    path: web::Path<Uuid>,
+    request: web::Json<CreateSerializedItemRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let item = db::create_serialized_item(&pool, path.into_inner(), request, &user.actor())
+        .await
+        .context("Failed to create serialized item")?
+        .ok_or_else(|| ApiError::not_found("Part"))?;
+    
+    Ok(HttpResponse::Created().json(item))
+}
+
+pub async fn get_serialized_item(path: web::Path<Uuid>, pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let item = db::get_serialized_item(&pool, path.into_inner())
+        .await
+        .context("Failed to fetch serialized item")?
+        .ok_or_else(|| ApiError::not_found("Serialized item"))?;
+    
+    Ok(HttpResponse::Ok().json(item))
+}
+
+pub async fn update_serialized_item(
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateSerializedItemRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditInventory)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let item = db::update_serialized_item(&pool, path.into_inner(), request, &user.actor())
+        .await
+        .context("Failed to update serialized item")?
+        .ok_or_else(|| ApiError::not_found("Serialized item"))?;
+    
+    Ok(HttpResponse::Ok().json(item))
+}
+
+pub async fn assign_serialized_item(
+    path: web::Path<Uuid>,
+    request: web::Json<AssignSerializedItemRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::EditPcs)?;
+    
+    let item = db::assign_serialized_item(&pool, path.into_inner(), request.into_inner(), &user.actor())
+        .await
+        .context("Failed to assign serialized item")?
+        .ok_or_else(|| ApiError::not_found("Serialized item"))?;
+    
+    Ok(HttpResponse::Ok().json(item))
+}
+
+pub async fn lookup_serial(
+    query: web::Query<SerialLookupQuery>,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    query.validate()?;
+    
+    let locations = db::lookup_serial(&pool, &query.serial)
+        .await
+        .context("Failed to look up serial number")?;
+    
+    Ok(HttpResponse::Ok().json(locations))
+}
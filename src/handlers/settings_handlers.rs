@@ -0,0 +1,56 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::models::{InventorySettingsRequest, ListingSettingsRequest, Permission};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
+
+pub async fn get_listing_settings(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let settings = db::get_listing_settings(&pool)
+        .await
+        .context("Failed to fetch listing settings")?;
+    
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+pub async fn update_listing_settings(
+    request: web::Json<ListingSettingsRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::SetPrices)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let settings = db::update_listing_settings(&pool, request, &user.actor())
+        .await
+        .context("Failed to update listing settings")?;
+    
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+pub async fn get_inventory_settings(pool: web::Data<PgPool>) -> Result<HttpResponse, ApiError> {
+    let settings = db::get_inventory_settings(&pool)
+        .await
+        .context("Failed to fetch inventory settings")?;
+    
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+pub async fn update_inventory_settings(
+    request: web::Json<InventorySettingsRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::SetPrices)?;
+    
+    let settings = db::update_inventory_settings(&pool, request.into_inner(), &user.actor())
+        .await
+        .context("Failed to update inventory settings")?;
+    
+    Ok(HttpResponse::Ok().json(settings))
+}
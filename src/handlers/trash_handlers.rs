@@ -0,0 +1,28 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::models::{Buyer, Permission, Trash};
+use crate::auth::AuthUser;
+use crate::db;
+use crate::error::ApiError;
+
+pub async fn list_trash(
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    let pcs = db::get_deleted_pcs(&pool)
+        .await
+        .context("Failed to fetch deleted PCs")?;
+    let parts = db::get_deleted_parts(&pool)
+        .await
+        .context("Failed to fetch deleted parts")?;
+    let mut buyers = db::get_deleted_buyers(&pool)
+        .await
+        .context("Failed to fetch deleted buyers")?;
+    if !user.can(Permission::ViewBuyerContacts) {
+        buyers = buyers.into_iter().map(Buyer::without_contact_details).collect();
+    }
+    
+    Ok(HttpResponse::Ok().json(Trash { pcs, parts, buyers }))
+}
@@ -0,0 +1,62 @@
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{CreateUserRequest, UpdateUserRequest, Permission};
+use crate::auth::{self, AuthUser};
+use crate::db;
+use crate::error::ApiError;
+use crate::validation::Validate;
+
+pub async fn list_users(
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageUsers)?;
+    
+    let users = db::get_all_users(&pool)
+        .await
+        .context("Failed to fetch users")?;
+    
+    Ok(HttpResponse::Ok().json(users))
+}
+
+pub async fn create_user(
+    request: web::Json<CreateUserRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageUsers)?;
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let password_hash = auth::hash_password(&request.password)?;
+    let user = db::create_user(&pool, &request, &password_hash)
+        .await
+        .context("Failed to create user")?;
+    
+    Ok(HttpResponse::Created().json(user))
+}
+
+pub async fn update_user(
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateUserRequest>,
+    user: AuthUser,
+    pool: web::Data<PgPool>
+) -> Result<HttpResponse, ApiError> {
+    user.require(Permission::ManageUsers)?;
+    
+    let user_id = path.into_inner();
+    
+    let request = request.into_inner();
+    request.validate()?;
+    
+    let updated = db::update_user(&pool, user_id, request)
+        .await
+        .context("Failed to update user")?
+        .ok_or_else(|| ApiError::not_found("User"))?;
+    
+    Ok(HttpResponse::Ok().json(updated))
+}
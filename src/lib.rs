@@ -1,3 +1,12 @@
 pub mod models;
 pub mod handlers;
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod error;
+pub mod validation;
+pub mod auth;
+pub mod spreadsheet;
+pub mod compatibility;
+pub mod costing;
+pub mod pricing;
+pub mod purchasing;
+pub mod reorder;
\ No newline at end of file
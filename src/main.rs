@@ -1,4 +1,4 @@
-use actix_web::{web, App, HttpServer, Result, HttpResponse, middleware::Logger};
+use actix_web::{web, App, HttpServer, Result, HttpResponse, http::header, middleware::{from_fn, Logger}};
 use actix_cors::Cors;
 use sqlx::PgPool;
 use dotenv::dotenv;
@@ -7,8 +7,18 @@ use std::env;
 pub mod models;
 pub mod handlers;
 pub mod db;
+pub mod error;
+pub mod validation;
+pub mod auth;
+pub mod spreadsheet;
+pub mod compatibility;
+pub mod costing;
+pub mod pricing;
+pub mod purchasing;
+pub mod reorder;
 
-use handlers::{pc_handlers, inventory_handlers, buyer_handlers, report_handlers};
+use handlers::{pc_handlers, inventory_handlers, buyer_handlers, report_handlers, auth_handlers, user_handlers, audit_handlers, trash_handlers, import_handlers, export_handlers, category_handlers, settings_handlers, purchasing_handlers, serial_handlers};
+use models::{Actor, ImportReport, RowOutcome};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -28,28 +38,79 @@ async fn main() -> std::io::Result<()> {
         .await
         .expect("Failed to run migrations");
 
+    // `pc-inventory-backend import-xlsx <file> [--dry-run]` imports a tracker workbook instead of serving
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.as_slice() {
+        [command, path] if command == "import-xlsx" => return import_xlsx(&pool, path, false).await,
+        [command, path, flag] if command == "import-xlsx" && flag == "--dry-run" => {
+            return import_xlsx(&pool, path, true).await
+        }
+        _ => {}
+    }
+
+    // Comma-separated list of origins allowed to call the API, or `*` for any
+    let allowed_origins: Vec<String> = env::var("CORS_ALLOWED_ORIGINS")
+        .unwrap_or_else(|_| "http://localhost:3000".to_string())
+        .split(',')
+        .map(|origin| origin.trim().to_string())
+        .filter(|origin| !origin.is_empty())
+        .collect();
+
     println!("🚀 Server starting on http://localhost:8080");
 
     HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header();
+        let cors = cors_policy(&allowed_origins);
 
         App::new()
             .app_data(web::Data::new(pool.clone()))
+            .app_data(web::JsonConfig::default().error_handler(error::json_error_handler))
+            .app_data(web::QueryConfig::default().error_handler(error::query_error_handler))
             .wrap(cors)
             .wrap(Logger::default())
+            .service(
+                web::scope("/api/auth")
+                    .route("/setup", web::post().to(auth_handlers::setup))
+                    .route("/login", web::post().to(auth_handlers::login))
+                    .service(
+                        web::scope("")
+                            .wrap(from_fn(auth::require_auth))
+                            .route("/logout", web::post().to(auth_handlers::logout))
+                            .route("/me", web::get().to(auth_handlers::me))
+                            .route("/password", web::post().to(auth_handlers::change_password))
+                    )
+            )
             .service(
                 web::scope("/api")
+                    .wrap(from_fn(auth::require_auth))
+                    .service(
+                        web::scope("/users")
+                            .route("", web::get().to(user_handlers::list_users))
+                            .route("", web::post().to(user_handlers::create_user))
+                            .route("/{id}", web::put().to(user_handlers::update_user))
+                    )
                     .service(
                         web::scope("/pcs")
                             .route("", web::get().to(pc_handlers::list_pcs))
                             .route("", web::post().to(pc_handlers::create_pc))
+                            .route("/stale", web::get().to(pc_handlers::stale_listings))
+                            .route("/stale/price-drops", web::post().to(pc_handlers::apply_price_drops))
                             .route("/{id}", web::get().to(pc_handlers::get_pc))
                             .route("/{id}", web::put().to(pc_handlers::update_pc))
                             .route("/{id}", web::delete().to(pc_handlers::delete_pc))
+                            .route("/{id}/restore", web::post().to(pc_handlers::restore_pc))
+                            .route("/{id}/purge", web::delete().to(pc_handlers::purge_pc))
                             .route("/{id}/sell", web::post().to(pc_handlers::sell_pc))
+                            .route("/{id}/list", web::post().to(pc_handlers::list_pc))
+                            .route("/{id}/unlist", web::post().to(pc_handlers::unlist_pc))
+                            .route("/{id}/archive", web::post().to(pc_handlers::archive_pc))
+                            .route("/{id}/history", web::get().to(pc_handlers::status_history))
+                            .route("/{id}/price-history", web::get().to(pc_handlers::price_history))
+                            .route("/{id}/components", web::get().to(pc_handlers::list_components))
+                            .route("/{id}/components", web::post().to(pc_handlers::add_component))
+                            .route("/{id}/components/{component_id}", web::put().to(pc_handlers::update_component))
+                            .route("/{id}/components/{component_id}", web::delete().to(pc_handlers::delete_component))
+                            .route("/{id}/compatibility", web::get().to(pc_handlers::compatibility))
+                            .route("/{id}/price-suggestion", web::get().to(pc_handlers::price_suggestion))
                     )
                     .service(
                         web::scope("/inventory")
@@ -57,19 +118,94 @@ async fn main() -> std::io::Result<()> {
                             .route("", web::post().to(inventory_handlers::create_part))
                             .route("/{id}", web::put().to(inventory_handlers::update_part))
                             .route("/{id}", web::delete().to(inventory_handlers::delete_part))
+                            .route("/{id}/restore", web::post().to(inventory_handlers::restore_part))
+                            .route("/{id}/purge", web::delete().to(inventory_handlers::purge_part))
+                            .route("/{id}/price-history", web::get().to(inventory_handlers::price_history))
+                            .route("/{id}/movements", web::get().to(inventory_handlers::stock_movements))
+                            .route("/{id}/movements", web::post().to(inventory_handlers::create_stock_movement))
+                            .route("/{id}/lots", web::get().to(inventory_handlers::lots))
+                            .route("/{id}/serials", web::get().to(serial_handlers::list_part_serials))
+                            .route("/{id}/serials", web::post().to(serial_handlers::create_serialized_item))
                             .route("/low-stock", web::get().to(inventory_handlers::low_stock))
+                            .route("/stock-discrepancies", web::get().to(inventory_handlers::stock_discrepancies))
+                    )
+                    .service(
+                        web::scope("/listing-settings")
+                            .route("", web::get().to(settings_handlers::get_listing_settings))
+                            .route("", web::put().to(settings_handlers::update_listing_settings))
+                    )
+                    .service(
+                        web::scope("/inventory-settings")
+                            .route("", web::get().to(settings_handlers::get_inventory_settings))
+                            .route("", web::put().to(settings_handlers::update_inventory_settings))
+                    )
+                    .service(
+                        web::scope("/component-categories")
+                            .route("", web::get().to(category_handlers::list_categories))
+                            .route("", web::post().to(category_handlers::create_category))
+                            .route("/{slug}", web::put().to(category_handlers::update_category))
+                    )
+                    .service(
+                        web::scope("/suppliers")
+                            .route("", web::get().to(purchasing_handlers::list_suppliers))
+                            .route("", web::post().to(purchasing_handlers::create_supplier))
+                            .route("/{id}", web::get().to(purchasing_handlers::get_supplier))
+                            .route("/{id}", web::put().to(purchasing_handlers::update_supplier))
+                    )
+                    .service(
+                        web::scope("/purchase-orders")
+                            .route("", web::get().to(purchasing_handlers::list_purchase_orders))
+                            .route("", web::post().to(purchasing_handlers::create_purchase_order))
+                            .route("/{id}", web::get().to(purchasing_handlers::get_purchase_order))
+                            .route("/{id}", web::put().to(purchasing_handlers::update_purchase_order))
+                            .route("/{id}/receive", web::post().to(purchasing_handlers::receive_purchase_order))
+                            .route("/{id}/cancel", web::post().to(purchasing_handlers::cancel_purchase_order))
+                    )
+                    .service(
+                        web::scope("/serials")
+                            .route("/lookup", web::get().to(serial_handlers::lookup_serial))
+                            .route("/{id}", web::get().to(serial_handlers::get_serialized_item))
+                            .route("/{id}", web::put().to(serial_handlers::update_serialized_item))
+                            .route("/{id}/assign", web::put().to(serial_handlers::assign_serialized_item))
                     )
                     .service(
                         web::scope("/buyers")
                             .route("", web::get().to(buyer_handlers::list_buyers))
                             .route("", web::post().to(buyer_handlers::create_buyer))
+                            .route("/duplicates", web::get().to(buyer_handlers::duplicate_buyers))
+                            .route("/{id}", web::get().to(buyer_handlers::get_buyer))
+                            .route("/{id}", web::put().to(buyer_handlers::update_buyer))
+                            .route("/{id}", web::delete().to(buyer_handlers::delete_buyer))
+                            .route("/{id}/merge", web::post().to(buyer_handlers::merge_buyers))
+                            .route("/{id}/restore", web::post().to(buyer_handlers::restore_buyer))
+                            .route("/{id}/purge", web::delete().to(buyer_handlers::purge_buyer))
+                            .route("/{id}/profile", web::get().to(buyer_handlers::buyer_profile))
                             .route("/{id}/purchases", web::get().to(buyer_handlers::buyer_purchases))
                     )
                     .service(
                         web::scope("/reports")
                             .route("/monthly", web::get().to(report_handlers::monthly_summary))
                             .route("/profit-analysis", web::get().to(report_handlers::profit_analysis))
+                            .route("/top-buyers", web::get().to(report_handlers::top_buyers))
+                            .route("/discounts", web::get().to(report_handlers::discounts))
+                            .route("/reorder", web::get().to(report_handlers::reorder))
                     )
+                    .service(
+                        web::scope("/import")
+                            .app_data(web::PayloadConfig::new(import_handlers::MAX_IMPORT_BYTES))
+                            .route("/xlsx", web::post().to(import_handlers::import_xlsx))
+                            .route("/csv/{entity}", web::post().to(import_handlers::import_csv))
+                    )
+                    .service(
+                        web::scope("/export")
+                            .route("/pcs", web::get().to(export_handlers::export_pcs))
+                            .route("/inventory", web::get().to(export_handlers::export_parts))
+                            .route("/buyers", web::get().to(export_handlers::export_buyers))
+                            .route("/monthly", web::get().to(export_handlers::export_monthly_summary))
+                            .route("/xlsx", web::get().to(export_handlers::export_xlsx))
+                    )
+                    .route("/audit", web::get().to(audit_handlers::list_audit))
+                    .route("/trash", web::get().to(trash_handlers::list_trash))
             )
             .route("/health", web::get().to(health_check))
     })
@@ -78,6 +214,66 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
+async fn import_xlsx(pool: &PgPool, path: &str, dry_run: bool) -> std::io::Result<()> {
+    let bytes = std::fs::read(path)?;
+    let rows = match spreadsheet::xlsx::read_workbook(&bytes) {
+        Ok(rows) => rows,
+        Err(errors) => {
+            eprintln!("❌ {}", errors);
+            std::process::exit(1);
+        }
+    };
+
+    let report = db::import_rows(pool, rows, dry_run, &Actor::system())
+        .await
+        .map_err(std::io::Error::other)?;
+    print_import_report(&report);
+
+    if report.failed > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_import_report(report: &ImportReport) {
+    for row in report.rows.iter().filter(|row| row.outcome != RowOutcome::Imported) {
+        println!(
+            "{} row {} ({}): {:?} - {}",
+            row.sheet,
+            row.row,
+            row.name.as_deref().unwrap_or("-"),
+            row.outcome,
+            row.message.as_deref().unwrap_or_default()
+        );
+    }
+
+    println!(
+        "Imported {}, skipped {}, failed {}; {} new buyers",
+        report.imported, report.skipped, report.failed, report.buyers_created
+    );
+    if report.committed {
+        println!("✅ Import committed");
+    } else if report.failed == 0 {
+        println!("✅ Dry run passed; nothing was written");
+    } else {
+        println!("❌ Nothing was written; fix the failed rows and run the import again");
+    }
+}
+
+fn cors_policy(allowed_origins: &[String]) -> Cors {
+    let cors = if allowed_origins.iter().any(|origin| origin == "*") {
+        Cors::default().allow_any_origin()
+    } else {
+        allowed_origins
+            .iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+    };
+
+    cors.allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
+        .allowed_headers(vec![header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT])
+        .max_age(3600)
+}
+
 async fn health_check() -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "status": "ok",
@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::FromRow;
+
+use super::SortOrder;
+
+/// Who made a change, recorded in the status history and the audit log
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub user_id: Option<Uuid>,
+    pub name: String,
+}
+
+impl Actor {
+    /// Changes made outside a user request, e.g. imports run from the command line
+    pub fn system() -> Self {
+        Actor {
+            user_id: None,
+            name: "system".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEntity {
+    Pc,
+    Component,
+    Part,
+    Buyer,
+    Category,
+    Settings,
+    Supplier,
+    #[serde(rename = "purchase_order")]
+    PurchaseOrder,
+    #[serde(rename = "serialized_item")]
+    SerializedItem,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Pc => "pc",
+            AuditEntity::Component => "component",
+            AuditEntity::Part => "part",
+            AuditEntity::Buyer => "buyer",
+            AuditEntity::Category => "category",
+            AuditEntity::Settings => "settings",
+            AuditEntity::Supplier => "supplier",
+            AuditEntity::PurchaseOrder => "purchase_order",
+            AuditEntity::SerializedItem => "serialized_item",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Sell,
+    Restore,
+    Purge,
+    Merge,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Sell => "sell",
+            AuditAction::Restore => "restore",
+            AuditAction::Purge => "purge",
+            AuditAction::Merge => "merge",
+        }
+    }
+}
+
+/// One recorded change. Creates carry only `after` and purges only `before`;
+/// everything else, including moving to and from the trash, carries just the
+/// fields that changed on each side. A merged buyer's event compares it with
+/// the buyer it was merged into, so `after.id` is the survivor.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub action: String,
+    pub actor_id: Option<Uuid>,
+    pub actor: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Filters for `GET /api/audit`; dates are inclusive and newest events come first by default
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuditListQuery {
+    pub entity_type: Option<AuditEntity>,
+    pub entity_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub actor: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub order: Option<SortOrder>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
@@ -3,7 +3,7 @@ use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use sqlx::FromRow;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Buyer {
     pub id: Uuid,
     pub name: String,
@@ -11,6 +11,20 @@ pub struct Buyer {
     pub email: Option<String>,
     pub phone: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// Set while the buyer is in the trash
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Buyer {
+    /// Hides contact, email and phone from roles that may not see them
+    pub fn without_contact_details(self) -> Self {
+        Buyer {
+            contact: None,
+            email: None,
+            phone: None,
+            ..self
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,4 +33,43 @@ pub struct CreateBuyerRequest {
     pub contact: Option<String>,
     pub email: Option<String>,
     pub phone: Option<String>,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateBuyerRequest {
+    pub name: Option<String>,
+    pub contact: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// Body for `POST /api/buyers/{id}/merge`; the buyer in the path survives
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeBuyersRequest {
+    pub duplicate_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeBuyersResult {
+    /// The surviving buyer, with contact details it was missing copied over from the duplicates
+    pub buyer: Buyer,
+    pub merged_ids: Vec<Uuid>,
+    pub pcs_moved: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateMatch {
+    Name,
+    Email,
+    Phone,
+}
+
+/// Buyers that share a normalized name, email or phone number
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateBuyerGroup {
+    pub matched_on: DuplicateMatch,
+    /// The normalized value they share
+    pub value: String,
+    pub buyers: Vec<Buyer>,
+}
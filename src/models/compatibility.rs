@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The build won't work as specced; a PC with errors can't be listed
+    Error,
+    /// Works, but worth a second look, e.g. a PSU with little headroom
+    Warning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityIssue {
+    pub severity: IssueSeverity,
+    /// Stable identifier, e.g. `socket_mismatch` or `psu_insufficient`
+    pub code: String,
+    pub message: String,
+    /// The components involved
+    pub component_ids: Vec<Uuid>,
+}
+
+/// Result of `GET /api/pcs/{id}/compatibility`. Checks only run where both
+/// sides have the specs they compare, so a build without specs has no issues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompatibilityReport {
+    pub pc_id: Uuid,
+    /// No issue is an error
+    pub compatible: bool,
+    /// Sum of the components' TDPs plus an allowance for the rest of the system;
+    /// `None` when no component has a TDP
+    pub estimated_power_draw_watts: Option<i32>,
+    /// Rated output of the build's PSU, if it has one with a wattage
+    pub psu_wattage: Option<i32>,
+    pub issues: Vec<CompatibilityIssue>,
+}
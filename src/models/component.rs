@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::FromRow;
+use sqlx::{types::Json, FromRow};
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Component {
@@ -11,27 +12,170 @@ pub struct Component {
     pub component_name: String,
     pub cost: Decimal,
     pub notes: Option<String>,
+    pub inventory_part_id: Option<Uuid>,
+    /// The inventory lot the part was taken from
+    pub lot_id: Option<Uuid>,
+    pub specs: Json<ComponentSpecs>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
-#[sqlx(type_name = "component_type", rename_all = "lowercase")]
-pub enum ComponentType {
-    Cpu,
-    Gpu,
-    Motherboard,
-    Ram,
-    Storage1,
-    Storage2,
-    Psu,
-    Case,
-    CpuCooler,
-    Additional,
+/// Spec attributes the compatibility check compares; which apply depends on
+/// the category, e.g. `socket` on a CPU and motherboard or `wattage` on a PSU
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentSpecs {
+    /// CPU and motherboard socket, e.g. `AM4` or `LGA1200`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
+    /// Motherboard and RAM memory type, e.g. `DDR4`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_type: Option<String>,
+    /// Motherboard size, or the largest board a case takes: `E-ATX`, `ATX`, `Micro-ATX` or `Mini-ITX`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub form_factor: Option<String>,
+    /// Power the component draws under load, e.g. a CPU's or GPU's TDP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tdp_watts: Option<i32>,
+    /// A PSU's rated output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wattage: Option<i32>,
+    /// A GPU's length
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_mm: Option<i32>,
+    /// The longest GPU a case fits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gpu_length_mm: Option<i32>,
+}
+
+/// A component category slug, e.g. `gpu` or `case_fan`, referencing
+/// `component_categories`. Input is normalised, so `"GPU"`, `"Case Fan"` and
+/// the older enum names such as `"CpuCooler"` or `"Storage1"` are accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, sqlx::Type)]
+#[serde(transparent)]
+#[sqlx(transparent)]
+pub struct ComponentType(String);
+
+/// Former enum values and common spellings, by normalised form
+const LEGACY_SLUGS: [(&str, &str); 17] = [
+    ("storage1", "storage"),
+    ("storage2", "storage"),
+    ("storage_1", "storage"),
+    ("storage_2", "storage"),
+    ("additional", "other"),
+    ("additional_parts", "other"),
+    ("cpucooler", "cpu_cooler"),
+    ("cooler", "cpu_cooler"),
+    ("mb", "motherboard"),
+    ("fan", "case_fan"),
+    ("fans", "case_fan"),
+    ("case_fans", "case_fan"),
+    ("wifi", "wifi_card"),
+    ("wi_fi", "wifi_card"),
+    ("wi_fi_card", "wifi_card"),
+    ("cables", "cable"),
+    ("os", "os_license"),
+];
+
+impl ComponentType {
+    pub fn new(input: &str) -> Self {
+        let mut slug = String::new();
+        let mut previous: Option<char> = None;
+        for c in input.chars() {
+            if c.is_ascii_alphanumeric() {
+                // CamelCase boundaries, as in the old enum names
+                if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) {
+                    slug.push('_');
+                }
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('_') {
+                slug.push('_');
+            }
+            previous = Some(c);
+        }
+        let slug = slug.trim_end_matches('_');
+
+        let slug = match LEGACY_SLUGS.iter().find(|(legacy, _)| *legacy == slug) {
+            Some((_, current)) => current,
+            None => slug,
+        };
+        ComponentType(slug.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for ComponentType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|input| ComponentType::new(&input))
+    }
+}
+
+impl std::fmt::Display for ComponentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A row of `component_categories`, the data-driven list of component types
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ComponentCategory {
+    pub id: Uuid,
+    pub slug: ComponentType,
+    pub name: String,
+    /// Position in lists and export columns
+    pub sort_order: i32,
+    /// How many of this category one PC takes, e.g. one CPU; `None` for no limit
+    pub max_per_pc: Option<i32>,
+    /// Default low-stock level for the category's parts
+    pub reorder_point: Option<i32>,
+    /// Default stock to reorder the category's parts up to
+    pub target_stock: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateComponentCategoryRequest {
+    pub slug: ComponentType,
+    pub name: String,
+    pub sort_order: Option<i32>,
+    pub max_per_pc: Option<i32>,
+    pub reorder_point: Option<i32>,
+    pub target_stock: Option<i32>,
+}
+
+/// The slug is fixed once components and parts reference it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateComponentCategoryRequest {
+    pub name: Option<String>,
+    pub sort_order: Option<i32>,
+    pub max_per_pc: Option<i32>,
+    pub reorder_point: Option<i32>,
+    pub target_stock: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateComponentRequest {
     pub component_type: ComponentType,
     pub component_name: String,
-    pub cost: Decimal,
+    /// Defaults to the part's `buy_in_price` when `inventory_part_id` is set
+    pub cost: Option<Decimal>,
+    pub notes: Option<String>,
+    /// Inventory part consumed by this component; one unit is taken from stock
+    pub inventory_part_id: Option<Uuid>,
+    #[serde(default)]
+    pub specs: ComponentSpecs,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateComponentRequest {
+    pub component_type: Option<ComponentType>,
+    pub component_name: Option<String>,
+    /// Defaults to the new part's `buy_in_price` when swapping `inventory_part_id`
+    pub cost: Option<Decimal>,
     pub notes: Option<String>,
-}
\ No newline at end of file
+    /// Swaps the component for another inventory part, returning the old one to stock
+    pub inventory_part_id: Option<Uuid>,
+    /// Merged into the current specs; keys left out are kept
+    pub specs: Option<ComponentSpecs>,
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use super::{CreateBuyerRequest, CreateComponentRequest, CreateInventoryPartRequest};
+
+/// A PC read from an import file together with the dates that decide its status
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedPc {
+    pub pc_name: String,
+    pub build_date: Option<NaiveDate>,
+    pub list_date: Option<NaiveDate>,
+    pub sale_date: Option<NaiveDate>,
+    pub intended_price: Option<Decimal>,
+    pub actual_sale_price: Option<Decimal>,
+    pub platform: Option<String>,
+    pub platform_reference: Option<String>,
+    pub notes: Option<String>,
+    /// Archived after walking through the statuses its dates imply
+    pub archived: bool,
+    /// Matched to an existing buyer by name, or created
+    pub buyer: Option<CreateBuyerRequest>,
+    pub components: Vec<CreateComponentRequest>,
+}
+
+#[derive(Debug)]
+pub enum ImportRecord {
+    Part(CreateInventoryPartRequest),
+    Pc(ImportedPc),
+    Buyer(CreateBuyerRequest),
+}
+
+/// What a CSV file passed to `POST /api/import/csv/{entity}` holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvEntity {
+    Pcs,
+    Inventory,
+    Buyers,
+}
+
+impl CsvEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CsvEntity::Pcs => "pcs",
+            CsvEntity::Inventory => "inventory",
+            CsvEntity::Buyers => "buyers",
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportQuery {
+    /// Check every row, including conflicts with existing records, then roll back
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One data row of an import file. `record` is the row's error message when
+/// it could not be read, e.g. a date in an unknown format.
+#[derive(Debug)]
+pub struct ImportRow {
+    /// Sheet name, or the entity for a CSV file
+    pub sheet: String,
+    /// Row number as shown by a spreadsheet program, counting the header
+    pub row: u32,
+    pub name: Option<String>,
+    pub record: Result<ImportRecord, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowOutcome {
+    Imported,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    pub sheet: String,
+    pub row: u32,
+    pub outcome: RowOutcome,
+    pub name: Option<String>,
+    /// Why the row was skipped or failed
+    pub message: Option<String>,
+}
+
+/// Result of an import. The import runs in one transaction, so when any row
+/// fails, or on a dry run, nothing is written and `committed` is false; the
+/// other rows still report what would have happened.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub committed: bool,
+    pub dry_run: bool,
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Buyer rows plus buyers created for PCs whose buyer wasn't found
+    pub buyers_created: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+impl ImportReport {
+    pub fn push(&mut self, result: ImportRowResult) {
+        match result.outcome {
+            RowOutcome::Imported => self.imported += 1,
+            RowOutcome::Skipped => self.skipped += 1,
+            RowOutcome::Failed => self.failed += 1,
+        }
+        self.rows.push(result);
+    }
+}
@@ -4,27 +4,37 @@ use rust_decimal::Decimal;
 use chrono::{DateTime, Utc};
 use sqlx::FromRow;
 
+use super::ComponentType;
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct InventoryPart {
     pub id: Uuid,
-    pub component_type: String,
+    pub component_type: ComponentType,
     pub component_name: String,
     pub buy_in_price: Option<Decimal>,
     pub typical_sell_price: Option<Decimal>,
     pub quantity_available: i32,
+    /// Stock at or below this is low; falls back to the category's
+    pub reorder_point: Option<i32>,
+    /// How many to have on the shelf after reordering; falls back to the category's
+    pub target_stock: Option<i32>,
     pub notes: Option<String>,
     pub purchase_link: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set while the part is in the trash
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateInventoryPartRequest {
-    pub component_type: String,
+    pub component_type: ComponentType,
     pub component_name: String,
     pub buy_in_price: Option<Decimal>,
     pub typical_sell_price: Option<Decimal>,
     pub quantity_available: Option<i32>,
+    pub reorder_point: Option<i32>,
+    pub target_stock: Option<i32>,
     pub notes: Option<String>,
     pub purchase_link: Option<String>,
 }
@@ -34,7 +44,141 @@ pub struct UpdateInventoryPartRequest {
     pub component_name: Option<String>,
     pub buy_in_price: Option<Decimal>,
     pub typical_sell_price: Option<Decimal>,
+    /// Why a price changed, kept in the price history
+    pub price_change_reason: Option<String>,
     pub quantity_available: Option<i32>,
+    pub reorder_point: Option<i32>,
+    pub target_stock: Option<i32>,
     pub notes: Option<String>,
     pub purchase_link: Option<String>,
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "stock_movement_type", rename_all = "snake_case")]
+pub enum StockMovementType {
+    /// Bought in or delivered
+    Receive,
+    /// Built into a PC
+    Consume,
+    /// Sold on its own
+    Sell,
+    /// Back on the shelf, from a build or a buyer
+    Return,
+    /// Stock count correction, either way
+    Adjust,
+    /// Faulty, lost or otherwise gone for good
+    WriteOff,
+}
+
+impl StockMovementType {
+    /// Whether the movement takes stock off the shelf; `Adjust` goes either way
+    pub fn removes_stock(self) -> bool {
+        matches!(self, StockMovementType::Consume | StockMovementType::Sell | StockMovementType::WriteOff)
+    }
+}
+
+/// One change to a part's stock
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct StockMovement {
+    pub id: Uuid,
+    pub part_id: Uuid,
+    pub movement_type: StockMovementType,
+    /// Signed: positive adds stock
+    pub quantity: i32,
+    /// `quantity_available` right after the movement
+    pub balance_after: i32,
+    /// The PC the part was built into or taken back out of
+    pub pc_id: Option<Uuid>,
+    /// The purchase order the part was received on
+    pub purchase_order_id: Option<Uuid>,
+    /// The lot the parts went into or came out of, when it was a single one
+    pub lot_id: Option<Uuid>,
+    /// Cost per part moved, by the costing method for parts taken off the shelf
+    pub unit_cost: Option<Decimal>,
+    pub reason: Option<String>,
+    pub reference: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Records a stock movement by hand. `quantity` is how many parts moved, so
+/// it is always positive except for `adjust`, where it is signed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateStockMovementRequest {
+    pub movement_type: StockMovementType,
+    pub quantity: i32,
+    /// Cost per part of stock added; defaults to the part's buy-in price
+    pub unit_cost: Option<Decimal>,
+    pub reason: Option<String>,
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "costing_method", rename_all = "snake_case")]
+pub enum CostingMethod {
+    /// At the cost of the oldest lot on the shelf
+    Fifo,
+    /// At the average cost of everything on the shelf
+    WeightedAverage,
+}
+
+/// How parts taken off the shelf are costed, which sets the cost of
+/// components built from inventory
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InventorySettings {
+    pub id: Uuid,
+    pub costing_method: CostingMethod,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InventorySettingsRequest {
+    pub costing_method: CostingMethod,
+}
+
+/// Parts put on the shelf together at one unit cost
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InventoryLot {
+    pub id: Uuid,
+    pub part_id: Uuid,
+    /// The purchase order the lot was received on
+    pub purchase_order_id: Option<Uuid>,
+    /// Empty while the buy-in price isn't known
+    pub unit_cost: Option<Decimal>,
+    pub quantity_received: i32,
+    pub quantity_remaining: i32,
+    pub received_at: DateTime<Utc>,
+}
+
+/// A part whose `quantity_available` disagrees with its stock movements
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct StockDiscrepancy {
+    pub part_id: Uuid,
+    pub component_type: ComponentType,
+    pub component_name: String,
+    pub quantity_available: i32,
+    /// Sum of the part's movements
+    pub ledger_quantity: i32,
+    /// `quantity_available - ledger_quantity`
+    pub difference: i32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LowStockQuery {
+    /// Overrides every part's reorder point
+    pub threshold: Option<i32>,
+}
+
+/// A part at or below its reorder point
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LowStockPart {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub part: InventoryPart,
+    /// The threshold that applied: the override, the part's, the category's or the default
+    pub effective_reorder_point: i32,
+    /// The part's target stock, or else the category's
+    pub effective_target_stock: Option<i32>,
+}
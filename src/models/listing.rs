@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use super::{ComponentType, PcStatus};
+
+pub const DEFAULT_PER_PAGE: i64 = 50;
+pub const MAX_PER_PAGE: i64 = 500;
+/// Highest page whose offset still fits in an `i64` at the largest page size
+pub const MAX_PAGE: i64 = i64::MAX / MAX_PER_PAGE;
+
+/// One page of a list endpoint along with the total number of matching rows
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// Offset pagination shared by the list queries; `page` is 1-based
+pub fn page_bounds(page: Option<i64>, per_page: Option<i64>) -> (i64, i64) {
+    let page = page.unwrap_or(1).clamp(1, MAX_PAGE);
+    let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    (page, per_page)
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PcSortField {
+    #[default]
+    CreatedAt,
+    PcName,
+    BuildDate,
+    ListDate,
+    SaleDate,
+    IntendedPrice,
+    ActualSalePrice,
+    TotalCost,
+    Profit,
+}
+
+impl PcSortField {
+    pub fn column(&self) -> &'static str {
+        match self {
+            PcSortField::CreatedAt => "created_at",
+            PcSortField::PcName => "pc_name",
+            PcSortField::BuildDate => "build_date",
+            PcSortField::ListDate => "list_date",
+            PcSortField::SaleDate => "sale_date",
+            PcSortField::IntendedPrice => "intended_price",
+            PcSortField::ActualSalePrice => "actual_sale_price",
+            PcSortField::TotalCost => "total_cost",
+            PcSortField::Profit => "profit",
+        }
+    }
+}
+
+/// Filters for `GET /api/pcs`. Price bounds apply to the sale price,
+/// falling back to the intended price for unsold PCs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PcListQuery {
+    pub status: Option<PcStatus>,
+    pub build_from: Option<NaiveDate>,
+    pub build_to: Option<NaiveDate>,
+    pub list_from: Option<NaiveDate>,
+    pub list_to: Option<NaiveDate>,
+    pub sale_from: Option<NaiveDate>,
+    pub sale_to: Option<NaiveDate>,
+    pub platform: Option<String>,
+    pub buyer_id: Option<Uuid>,
+    pub component_type: Option<ComponentType>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    pub q: Option<String>,
+    pub sort: Option<PcSortField>,
+    pub order: Option<SortOrder>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartSortField {
+    #[default]
+    ComponentType,
+    ComponentName,
+    BuyInPrice,
+    TypicalSellPrice,
+    QuantityAvailable,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl PartSortField {
+    /// Sorting by type keeps names in order within each type
+    pub fn columns(&self) -> &'static [&'static str] {
+        match self {
+            PartSortField::ComponentType => &["component_type", "component_name"],
+            PartSortField::ComponentName => &["component_name"],
+            PartSortField::BuyInPrice => &["buy_in_price"],
+            PartSortField::TypicalSellPrice => &["typical_sell_price"],
+            PartSortField::QuantityAvailable => &["quantity_available"],
+            PartSortField::CreatedAt => &["created_at"],
+            PartSortField::UpdatedAt => &["updated_at"],
+        }
+    }
+}
+
+/// Filters for `GET /api/inventory`. Price bounds apply to `buy_in_price`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PartListQuery {
+    pub component_type: Option<ComponentType>,
+    pub in_stock: Option<bool>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    pub q: Option<String>,
+    pub sort: Option<PartSortField>,
+    pub order: Option<SortOrder>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuyerSortField {
+    #[default]
+    Name,
+    Email,
+    CreatedAt,
+}
+
+impl BuyerSortField {
+    pub fn column(&self) -> &'static str {
+        match self {
+            BuyerSortField::Name => "name",
+            BuyerSortField::Email => "email",
+            BuyerSortField::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// Filters for `GET /api/buyers`. `q` matches name, contact, email or phone.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuyerListQuery {
+    pub q: Option<String>,
+    pub sort: Option<BuyerSortField>,
+    pub order: Option<SortOrder>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
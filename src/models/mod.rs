@@ -3,9 +3,27 @@ pub mod buyer;
 pub mod inventory;
 pub mod component;
 pub mod reports;
+pub mod listing;
+pub mod user;
+pub mod audit;
+pub mod trash;
+pub mod import;
+pub mod compatibility;
+pub mod pricing;
+pub mod purchasing;
+pub mod serial;
 
 pub use pc::*;
 pub use buyer::*;
 pub use inventory::*;
 pub use component::*;
-pub use reports::*;
\ No newline at end of file
+pub use reports::*;
+pub use listing::*;
+pub use user::*;
+pub use audit::*;
+pub use trash::*;
+pub use import::*;
+pub use compatibility::*;
+pub use pricing::*;
+pub use purchasing::*;
+pub use serial::*;
\ No newline at end of file
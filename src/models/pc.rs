@@ -25,9 +25,15 @@ pub struct Pc {
     pub status: PcStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set while the PC is in the trash
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Days since `list_date` while listed; `days_listed` is only filled in once the PC sells
+    #[sqlx(default)]
+    pub days_on_market: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
 #[sqlx(type_name = "pc_status", rename_all = "lowercase")]
 pub enum PcStatus {
     Building,
@@ -36,6 +42,43 @@ pub enum PcStatus {
     Archived,
 }
 
+impl PcStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PcStatus::Building => "building",
+            PcStatus::Listed => "listed",
+            PcStatus::Sold => "sold",
+            PcStatus::Archived => "archived",
+        }
+    }
+
+    /// Allowed moves: building → listed → sold, with unlisting back to
+    /// building, relisting a sold PC when the sale is returned, and archiving
+    /// from anywhere. Archived PCs can only go back to building, which clears
+    /// any sale they had.
+    pub fn can_transition_to(&self, next: PcStatus) -> bool {
+        use PcStatus::*;
+
+        matches!(
+            (self, next),
+            (Building, Listed)
+                | (Building, Archived)
+                | (Listed, Building)
+                | (Listed, Sold)
+                | (Listed, Archived)
+                | (Sold, Listed)
+                | (Sold, Archived)
+                | (Archived, Building)
+        )
+    }
+}
+
+impl std::fmt::Display for PcStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreatePcRequest {
     pub pc_name: String,
@@ -51,6 +94,8 @@ pub struct UpdatePcRequest {
     pub build_date: Option<NaiveDate>,
     pub list_date: Option<NaiveDate>,
     pub intended_price: Option<Decimal>,
+    /// Why `intended_price` changed, kept in the price history
+    pub price_change_reason: Option<String>,
     pub notes: Option<String>,
     pub status: Option<PcStatus>,
 }
@@ -64,9 +109,34 @@ pub struct SellPcRequest {
     pub platform_reference: Option<String>,
 }
 
+/// Optional body for list/unlist/archive; the change is recorded against the signed-in user
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StatusChangeRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PcStatusChange {
+    pub id: Uuid,
+    pub pc_id: Uuid,
+    pub from_status: Option<PcStatus>,
+    pub to_status: PcStatus,
+    pub changed_by: Option<String>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PcWithComponents {
     #[serde(flatten)]
     pub pc: Pc,
     pub components: Vec<super::component::Component>,
-}
\ No newline at end of file
+}
+/// A live PC with its components and its buyer, as flattened into exports
+#[derive(Debug)]
+pub struct PcExport {
+    pub pc: Pc,
+    pub buyer_name: Option<String>,
+    pub buyer_contact: Option<String>,
+    pub components: Vec<super::component::Component>,
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::FromRow;
+
+use super::Pc;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PriceSuggestionQuery {
+    /// Profit as a percentage of total cost, like `profit_percentage`; defaults
+    /// to what comparable builds made
+    pub target_margin: Option<Decimal>,
+}
+
+/// A sold PC the suggestion was based on
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComparableBuild {
+    pub pc_id: Uuid,
+    pub pc_name: String,
+    pub total_cost: Decimal,
+    pub actual_sale_price: Decimal,
+    pub profit_percentage: Decimal,
+    pub days_listed: Option<i32>,
+    pub sale_date: Option<NaiveDate>,
+    /// 0 to 1, from the parts both builds share and how close their costs are
+    pub similarity: Decimal,
+    /// Parts both builds have, as `type: name`
+    pub shared_components: Vec<String>,
+}
+
+/// Result of `GET /api/pcs/{id}/price-suggestion`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceSuggestion {
+    pub pc_id: Uuid,
+    pub total_cost: Decimal,
+    pub target_margin: Decimal,
+    /// Total cost plus the target margin, in whole kroner
+    pub suggested_price: Decimal,
+    /// Margin the comparables sold at, weighted by similarity
+    pub comparable_margin: Option<Decimal>,
+    /// Similarity-weighted days listed of the comparables that sold at the
+    /// target margin or better; `None` when none did
+    pub expected_days_to_sell: Option<Decimal>,
+    /// Most similar first
+    pub comparables: Vec<ComparableBuild>,
+}
+
+/// When a listing counts as stale and the optional schedule that brings its price down
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ListingSettings {
+    pub id: Uuid,
+    pub stale_after_days: i32,
+    /// Once stale, the price drops by this percentage every `price_drop_interval_days`;
+    /// both are empty while the schedule is off
+    pub price_drop_percent: Option<Decimal>,
+    pub price_drop_interval_days: Option<i32>,
+    /// Drops stop at total cost plus this percentage
+    pub min_margin: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Replaces every listing setting, so leaving out the drop fields turns the schedule off
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListingSettingsRequest {
+    pub stale_after_days: i32,
+    pub price_drop_percent: Option<Decimal>,
+    pub price_drop_interval_days: Option<i32>,
+    #[serde(default)]
+    pub min_margin: Decimal,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StaleListingQuery {
+    /// Overrides `stale_after_days`; 0 returns every listed PC
+    pub days: Option<i32>,
+}
+
+/// A listed PC that has been on the market for at least the stale threshold
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StaleListing {
+    #[serde(flatten)]
+    pub pc: Pc,
+    pub next_price_drop: Option<PriceDrop>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceDrop {
+    pub due: NaiveDate,
+    pub price: Decimal,
+}
+
+/// One change to a PC's intended price
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PcPriceChange {
+    pub id: Uuid,
+    pub pc_id: Uuid,
+    pub old_price: Option<Decimal>,
+    pub new_price: Option<Decimal>,
+    /// Made by the price-drop schedule rather than by hand
+    pub scheduled: bool,
+    pub changed_by: Option<String>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "part_price_type", rename_all = "snake_case")]
+pub enum PartPriceType {
+    BuyIn,
+    TypicalSell,
+}
+
+/// One change to a part's buy-in or typical sell price
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PartPriceChange {
+    pub id: Uuid,
+    pub part_id: Uuid,
+    pub price_type: PartPriceType,
+    pub old_price: Option<Decimal>,
+    pub new_price: Option<Decimal>,
+    pub changed_by: Option<String>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
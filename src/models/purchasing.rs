@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use sqlx::FromRow;
+
+use super::ComponentType;
+
+/// Somewhere parts are bought: a web shop, a retailer or a second-hand seller
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Supplier {
+    pub id: Uuid,
+    pub name: String,
+    pub website: Option<String>,
+    pub contact: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSupplierRequest {
+    pub name: String,
+    pub website: Option<String>,
+    pub contact: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSupplierRequest {
+    pub name: Option<String>,
+    pub website: Option<String>,
+    pub contact: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "purchase_order_status", rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    Ordered,
+    PartiallyReceived,
+    Received,
+    Cancelled,
+}
+
+impl PurchaseOrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurchaseOrderStatus::Ordered => "ordered",
+            PurchaseOrderStatus::PartiallyReceived => "partially_received",
+            PurchaseOrderStatus::Received => "received",
+            PurchaseOrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether parts are still to arrive
+    pub fn is_open(&self) -> bool {
+        matches!(self, PurchaseOrderStatus::Ordered | PurchaseOrderStatus::PartiallyReceived)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PurchaseOrder {
+    pub id: Uuid,
+    pub supplier_id: Uuid,
+    /// The supplier's order number
+    pub order_reference: Option<String>,
+    pub order_date: NaiveDate,
+    pub expected_date: Option<NaiveDate>,
+    pub status: PurchaseOrderStatus,
+    pub shipping_cost: Decimal,
+    pub customs_cost: Decimal,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PurchaseOrderLine {
+    pub id: Uuid,
+    pub purchase_order_id: Uuid,
+    pub part_id: Uuid,
+    pub component_type: ComponentType,
+    pub component_name: String,
+    pub quantity_ordered: i32,
+    pub quantity_received: i32,
+    pub unit_cost: Decimal,
+    /// Unit cost plus the line's share of shipping and customs, split by value
+    pub landed_unit_cost: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderWithLines {
+    #[serde(flatten)]
+    pub order: PurchaseOrder,
+    pub lines: Vec<PurchaseOrderLine>,
+    /// Everything ordered plus shipping and customs
+    pub total_cost: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePurchaseOrderRequest {
+    pub supplier_id: Uuid,
+    pub order_reference: Option<String>,
+    /// Defaults to today
+    pub order_date: Option<NaiveDate>,
+    pub expected_date: Option<NaiveDate>,
+    pub shipping_cost: Option<Decimal>,
+    pub customs_cost: Option<Decimal>,
+    pub notes: Option<String>,
+    pub lines: Vec<CreatePurchaseOrderLineRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePurchaseOrderLineRequest {
+    pub part_id: Uuid,
+    pub quantity: i32,
+    pub unit_cost: Decimal,
+}
+
+/// Changes an open order; new shipping or customs costs are split over the lines again
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdatePurchaseOrderRequest {
+    pub order_reference: Option<String>,
+    pub expected_date: Option<NaiveDate>,
+    pub shipping_cost: Option<Decimal>,
+    pub customs_cost: Option<Decimal>,
+    pub notes: Option<String>,
+}
+
+/// Parts that arrived, which go into stock at their landed cost
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceivePurchaseOrderRequest {
+    pub lines: Vec<ReceiveLineRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiveLineRequest {
+    pub line_id: Uuid,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PurchaseOrderListQuery {
+    pub status: Option<PurchaseOrderStatus>,
+    pub supplier_id: Option<Uuid>,
+    /// Only open orders past their expected date
+    pub overdue: Option<bool>,
+}
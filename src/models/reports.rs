@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
 use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::NaiveDate;
+
+use super::ComponentType;
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct MonthlySummary {
@@ -18,4 +22,129 @@ pub struct ProfitAnalysis {
     pub avg_cost: Option<Decimal>,
     pub total_usage: i64,
     pub avg_profit_contribution: Option<Decimal>,
-}
\ No newline at end of file
+}
+
+/// What a buyer has bought over time, counting sold PCs that aren't in the trash
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct BuyerLifetimeValue {
+    pub buyer_id: Uuid,
+    pub name: String,
+    pub purchases: i64,
+    pub total_spent: Decimal,
+    pub total_profit: Decimal,
+    pub first_purchase: Option<NaiveDate>,
+    pub last_purchase: Option<NaiveDate>,
+    /// Needs at least two purchases
+    pub average_days_between_purchases: Option<Decimal>,
+    /// The platform they bought on most often, the most recent one on a tie
+    pub preferred_platform: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TopBuyer {
+    pub rank: i64,
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub buyer: BuyerLifetimeValue,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopBuyerRanking {
+    #[default]
+    TotalSpent,
+    TotalProfit,
+    Purchases,
+}
+
+impl TopBuyerRanking {
+    pub fn column(&self) -> &'static str {
+        match self {
+            TopBuyerRanking::TotalSpent => "total_spent",
+            TopBuyerRanking::TotalProfit => "total_profit",
+            TopBuyerRanking::Purchases => "purchases",
+        }
+    }
+}
+
+/// Query for `GET /api/reports/top-buyers`; returns the top 10 by total spent by default
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TopBuyersQuery {
+    pub rank_by: Option<TopBuyerRanking>,
+    pub limit: Option<i64>,
+}
+
+/// Filters for `GET /api/reports/discounts`; sale dates are inclusive
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DiscountReportQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// What a sold PC was first priced at against what it sold for
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SaleDiscount {
+    pub pc_id: Uuid,
+    pub pc_name: String,
+    pub sale_date: Option<NaiveDate>,
+    /// The first intended price in the PC's price history
+    pub original_price: Decimal,
+    /// The intended price when it sold
+    pub final_asking_price: Option<Decimal>,
+    pub actual_sale_price: Decimal,
+    /// Negative when the PC sold above its original price
+    pub discount: Decimal,
+    pub discount_percentage: Option<Decimal>,
+    /// How often the intended price changed after it was first set
+    pub price_changes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiscountReport {
+    pub sales: Vec<SaleDiscount>,
+    pub total_original_price: Decimal,
+    pub total_sale_price: Decimal,
+    pub total_discount: Decimal,
+    /// Total discount as a percentage of the total original price
+    pub discount_percentage: Option<Decimal>,
+}
+
+/// Options for `GET /api/reports/reorder`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReorderReportQuery {
+    /// Days of recent consumption the rate is worked out over; defaults to 90
+    pub days: Option<i32>,
+    /// Days of consumption to have in stock on top of the reorder point; defaults to 30
+    pub cover_days: Option<i32>,
+}
+
+/// A part that should be reordered and how many of it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReorderSuggestion {
+    pub part_id: Uuid,
+    pub component_type: ComponentType,
+    pub component_name: String,
+    pub quantity_available: i32,
+    pub reorder_point: i32,
+    pub target_stock: Option<i32>,
+    /// Built in or sold over the last `days`, net of returns
+    pub consumed: i32,
+    /// Still to arrive on open purchase orders, counted as stock
+    pub on_order: i32,
+    pub daily_consumption: Decimal,
+    /// How long the stock lasts at that rate; `None` while nothing is used
+    pub days_of_stock: Option<Decimal>,
+    pub suggested_quantity: i32,
+    /// Suggested quantity at the buy-in price
+    pub estimated_cost: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReorderReport {
+    pub days: i32,
+    pub cover_days: i32,
+    /// Fewest days of stock first
+    pub parts: Vec<ReorderSuggestion>,
+    /// Sum of the estimated costs that are known
+    pub total_estimated_cost: Decimal,
+}
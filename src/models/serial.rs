@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::FromRow;
+
+use super::{ComponentType, PcStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "item_condition", rename_all = "snake_case")]
+pub enum ItemCondition {
+    New,
+    LikeNew,
+    Used,
+    Refurbished,
+    Faulty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "item_test_status", rename_all = "snake_case")]
+pub enum TestStatus {
+    Untested,
+    Passed,
+    Failed,
+}
+
+/// One physical unit of an inventory part, known by its serial number
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SerializedItem {
+    pub id: Uuid,
+    pub part_id: Uuid,
+    pub serial_number: String,
+    pub condition: ItemCondition,
+    pub test_status: TestStatus,
+    pub acquired_on: Option<NaiveDate>,
+    /// Who it came from: a seller, a listing, a trade-in
+    pub acquired_from: Option<String>,
+    pub supplier_id: Option<Uuid>,
+    /// The component it was built in as; empty while it is on the shelf
+    pub component_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSerializedItemRequest {
+    pub serial_number: String,
+    /// Defaults to `new`
+    pub condition: Option<ItemCondition>,
+    /// Defaults to `untested`
+    pub test_status: Option<TestStatus>,
+    pub acquired_on: Option<NaiveDate>,
+    pub acquired_from: Option<String>,
+    pub supplier_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSerializedItemRequest {
+    pub serial_number: Option<String>,
+    pub condition: Option<ItemCondition>,
+    pub test_status: Option<TestStatus>,
+    pub acquired_on: Option<NaiveDate>,
+    pub acquired_from: Option<String>,
+    pub supplier_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+/// Puts a unit in a build as one of its components, or back on the shelf when
+/// `component_id` is empty
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignSerializedItemRequest {
+    pub component_id: Option<Uuid>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SerialLookupQuery {
+    /// Matched exactly, ignoring case and surrounding spaces
+    pub serial: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemLocation {
+    InStock,
+    /// In a PC that hasn't sold yet
+    InBuild,
+    Sold,
+}
+
+/// Where a serialized unit is now and, once sold, who bought it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SerialLocation {
+    #[serde(flatten)]
+    pub item: SerializedItem,
+    pub component_type: ComponentType,
+    pub component_name: String,
+    pub location: ItemLocation,
+    pub pc_id: Option<Uuid>,
+    pub pc_name: Option<String>,
+    pub pc_status: Option<PcStatus>,
+    pub sale_date: Option<NaiveDate>,
+    pub platform: Option<String>,
+    pub buyer_id: Option<Uuid>,
+    pub buyer_name: Option<String>,
+}
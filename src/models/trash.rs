@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Buyer, InventoryPart, Pc};
+
+/// Everything currently in the trash, most recently deleted first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Trash {
+    pub pcs: Vec<Pc>,
+    pub parts: Vec<InventoryPart>,
+    pub buyers: Vec<Buyer>,
+}
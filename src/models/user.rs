@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+pub enum UserRole {
+    Owner,
+    Builder,
+    Viewer,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Owner => "owner",
+            UserRole::Builder => "builder",
+            UserRole::Viewer => "viewer",
+        }
+    }
+
+    /// Every signed-in role can read PCs, inventory, reports and the trash; these are the actions that need more
+    pub fn can(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::EditPcs | Permission::EditInventory => {
+                matches!(self, UserRole::Owner | UserRole::Builder)
+            }
+            Permission::DeletePcs
+            | Permission::SetPrices
+            | Permission::ViewBuyerContacts
+            | Permission::ManageBuyers
+            | Permission::ManageUsers
+            | Permission::ViewAudit
+            | Permission::PurgeRecords
+            | Permission::ImportData
+            | Permission::ManageCategories => *self == UserRole::Owner,
+        }
+    }
+}
+
+/// Actions restricted by role
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Create PCs, edit their details and components, list/unlist/archive them
+    EditPcs,
+    /// Add, edit and remove inventory parts
+    EditInventory,
+    DeletePcs,
+    /// Change a PC's intended price or record its sale price
+    SetPrices,
+    ViewBuyerContacts,
+    ManageBuyers,
+    ManageUsers,
+    /// Read the audit log of every change
+    ViewAudit,
+    /// Permanently remove PCs, parts and buyers from the trash
+    PurgeRecords,
+    /// Load PCs, parts and buyers from a spreadsheet
+    ImportData,
+    /// Add component categories and rename or reorder them
+    ManageCategories,
+}
+
+/// An API account. The password hash never leaves the db layer.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct User {
+    pub id: Uuid,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub role: UserRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub display_name: Option<String>,
+    pub password: String,
+    /// Defaults to `viewer`; the account created by setup is always an owner
+    pub role: Option<UserRole>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateUserRequest {
+    pub display_name: Option<String>,
+    pub role: Option<UserRole>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// Returned on login; `token` goes in `Authorization: Bearer <token>`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    pub user: User,
+}
@@ -0,0 +1,130 @@
+//! Suggests listing prices from the PCs that have already sold and schedules price drops for stale listings
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::Decimal;
+
+use crate::models::{ComparableBuild, Component, ListingSettings, Pc, PcWithComponents, PriceDrop, PriceSuggestion};
+use crate::validation::ValidationErrors;
+
+/// How many of the most similar sold PCs a suggestion is based on
+const MAX_COMPARABLES: usize = 5;
+
+/// Target margin in percent when nothing has sold yet
+const DEFAULT_TARGET_MARGIN: i64 = 25;
+
+/// Prices `build` at `target_margin` and estimates how long it takes to sell,
+/// from `sold`, the sold PCs with a sale price and a cost
+pub fn suggest_price(
+    build: &PcWithComponents,
+    sold: &[PcWithComponents],
+    target_margin: Option<Decimal>,
+) -> Result<PriceSuggestion, ValidationErrors> {
+    let total_cost = build.pc.total_cost.filter(|cost| *cost > Decimal::ZERO).ok_or_else(|| {
+        ValidationErrors::single("components", "missing_cost", "PC needs component costs before it can be priced")
+    })?;
+
+    let parts = part_keys(&build.components);
+    let mut comparables: Vec<ComparableBuild> = sold
+        .iter()
+        .filter(|other| other.pc.id != build.pc.id)
+        .filter_map(|other| compare(total_cost, &parts, other))
+        .collect();
+    comparables.sort_by(|a, b| b.similarity.cmp(&a.similarity).then(b.sale_date.cmp(&a.sale_date)));
+    comparables.truncate(MAX_COMPARABLES);
+
+    let comparable_margin = weighted_average(comparables.iter().map(|comparable| (comparable, comparable.profit_percentage)));
+    let target_margin = target_margin
+        .or(comparable_margin)
+        .unwrap_or_else(|| Decimal::from(DEFAULT_TARGET_MARGIN));
+    let suggested_price = (total_cost * (Decimal::ONE_HUNDRED + target_margin) / Decimal::ONE_HUNDRED).round_dp(0);
+
+    let expected_days_to_sell = weighted_average(
+        comparables
+            .iter()
+            .filter(|comparable| comparable.profit_percentage >= target_margin)
+            .filter_map(|comparable| comparable.days_listed.map(|days| (comparable, Decimal::from(days)))),
+    )
+    .map(|days| days.round_dp(1));
+
+    Ok(PriceSuggestion {
+        pc_id: build.pc.id,
+        total_cost,
+        target_margin,
+        suggested_price,
+        comparable_margin,
+        expected_days_to_sell,
+        comparables,
+    })
+}
+
+/// The next drop the schedule in `settings` makes to a listed PC: due once
+/// the listing turns stale, then every interval after `last_drop`. `None` when
+/// the schedule is off, the PC has no price, or the price is at its floor.
+pub fn next_price_drop(settings: &ListingSettings, pc: &Pc, last_drop: Option<NaiveDate>) -> Option<PriceDrop> {
+    let (percent, interval) = (settings.price_drop_percent?, settings.price_drop_interval_days?);
+    let price = pc.intended_price?;
+    let due = match last_drop {
+        Some(last_drop) => last_drop + Duration::days(interval.into()),
+        None => pc.list_date? + Duration::days(settings.stale_after_days.into()),
+    };
+
+    let floor = (pc.total_cost.unwrap_or_default() * (Decimal::ONE_HUNDRED + settings.min_margin) / Decimal::ONE_HUNDRED).ceil();
+    let dropped = (price * (Decimal::ONE_HUNDRED - percent) / Decimal::ONE_HUNDRED).round_dp(0).max(floor);
+
+    (dropped < price).then_some(PriceDrop { due, price: dropped })
+}
+
+/// Scores a sold PC two-thirds on the share of parts both builds have and
+/// one-third on how close their total costs are
+fn compare(total_cost: Decimal, parts: &BTreeMap<String, &Component>, other: &PcWithComponents) -> Option<ComparableBuild> {
+    let other_cost = other.pc.total_cost.filter(|cost| *cost > Decimal::ZERO)?;
+    let actual_sale_price = other.pc.actual_sale_price?;
+
+    let other_parts = part_keys(&other.components);
+    let shared_components: Vec<String> = other_parts
+        .iter()
+        .filter(|(key, _)| parts.contains_key(*key))
+        .map(|(_, component)| format!("{}: {}", component.component_type, component.component_name))
+        .collect();
+    let all_parts = parts.keys().chain(other_parts.keys().filter(|key| !parts.contains_key(*key))).count();
+    let overlap = if all_parts == 0 {
+        Decimal::ZERO
+    } else {
+        Decimal::from(shared_components.len()) / Decimal::from(all_parts)
+    };
+    let closeness = total_cost.min(other_cost) / total_cost.max(other_cost);
+    let similarity = ((overlap * Decimal::TWO + closeness) / Decimal::from(3)).round_dp(3);
+
+    Some(ComparableBuild {
+        pc_id: other.pc.id,
+        pc_name: other.pc.pc_name.clone(),
+        total_cost: other_cost,
+        actual_sale_price,
+        profit_percentage: ((actual_sale_price - other_cost) / other_cost * Decimal::ONE_HUNDRED).round_dp(2),
+        days_listed: other.pc.days_listed,
+        sale_date: other.pc.sale_date,
+        similarity,
+        shared_components,
+    })
+}
+
+/// Components keyed by type and name, ignoring case and spacing, so the same
+/// part bought twice matches
+fn part_keys(components: &[Component]) -> BTreeMap<String, &Component> {
+    components
+        .iter()
+        .map(|component| {
+            let name = component.component_name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            (format!("{}:{}", component.component_type, name), component)
+        })
+        .collect()
+}
+
+fn weighted_average<'a>(values: impl Iterator<Item = (&'a ComparableBuild, Decimal)>) -> Option<Decimal> {
+    let (weighted, weights) = values.fold((Decimal::ZERO, Decimal::ZERO), |(weighted, weights), (comparable, value)| {
+        (weighted + comparable.similarity * value, weights + comparable.similarity)
+    });
+    (weights > Decimal::ZERO).then(|| (weighted / weights).round_dp(2))
+}
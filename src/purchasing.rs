@@ -0,0 +1,25 @@
+//! Splits a purchase order's shipping and customs over its lines into landed costs
+
+use rust_decimal::Decimal;
+
+/// Each line's unit cost plus its share of `extra_cost`, given the lines as
+/// quantity and unit cost. Shares go by line value, or by quantity when
+/// nothing on the order has a price.
+pub fn landed_unit_costs(extra_cost: Decimal, lines: &[(i32, Decimal)]) -> Vec<Decimal> {
+    let total_value: Decimal = lines.iter().map(|(quantity, unit_cost)| Decimal::from(*quantity) * unit_cost).sum();
+    let total_quantity: Decimal = lines.iter().map(|(quantity, _)| Decimal::from(*quantity)).sum();
+
+    lines
+        .iter()
+        .map(|(_, unit_cost)| {
+            let extra_per_unit = if total_value > Decimal::ZERO {
+                extra_cost * unit_cost / total_value
+            } else if total_quantity > Decimal::ZERO {
+                extra_cost / total_quantity
+            } else {
+                Decimal::ZERO
+            };
+            (unit_cost + extra_per_unit).round_dp(2)
+        })
+        .collect()
+}
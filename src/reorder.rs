@@ -0,0 +1,78 @@
+//! Works out which parts to reorder, and how many, from their stock levels and recent consumption
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::models::{ComponentType, ReorderSuggestion};
+
+/// Reorder point of parts that neither they nor their category set one for
+pub const DEFAULT_REORDER_POINT: i32 = 5;
+
+/// Days of consumption the reorder report looks back over
+pub const DEFAULT_CONSUMPTION_DAYS: i32 = 90;
+
+/// Furthest back the reorder report looks, a century of consumption
+pub const MAX_CONSUMPTION_DAYS: i32 = 36_500;
+
+/// Days of consumption a reorder covers on top of the reorder point
+pub const DEFAULT_COVER_DAYS: i32 = 30;
+
+/// A part's stock with the levels that apply to it and what it used recently
+#[derive(Debug)]
+pub struct StockLevel {
+    pub part_id: Uuid,
+    pub component_type: ComponentType,
+    pub component_name: String,
+    pub quantity_available: i32,
+    pub reorder_point: i32,
+    pub target_stock: Option<i32>,
+    pub consumed: i32,
+    /// Still to arrive on open purchase orders
+    pub on_order: i32,
+    pub buy_in_price: Option<Decimal>,
+}
+
+/// Suggests a reorder once the stock left after `cover_days` at the rate
+/// consumed over `days`, counting what is already on order, is at or below
+/// the reorder point. The order brings stock up to the target, or to that much
+/// consumption above the reorder point if more, and always above the reorder
+/// point. Parts already stocked up to that level get no suggestion.
+pub fn suggest_reorder(level: StockLevel, days: i32, cover_days: i32) -> Option<ReorderSuggestion> {
+    let daily_consumption = (Decimal::from(level.consumed.max(0)) / Decimal::from(days)).round_dp(2);
+    let expected_use = (Decimal::from(level.consumed.max(0)) * Decimal::from(cover_days) / Decimal::from(days))
+        .ceil()
+        .to_i32()
+        .unwrap_or(i32::MAX);
+    let incoming_stock = level.quantity_available.saturating_add(level.on_order);
+    if incoming_stock.saturating_sub(expected_use) > level.reorder_point {
+        return None;
+    }
+
+    let order_up_to = level
+        .target_stock
+        .unwrap_or_default()
+        .max(level.reorder_point.saturating_add(expected_use.max(1)));
+    let suggested_quantity = order_up_to.saturating_sub(incoming_stock);
+    if suggested_quantity <= 0 {
+        return None;
+    }
+    let days_of_stock = (level.consumed > 0).then(|| {
+        (Decimal::from(level.quantity_available) * Decimal::from(days) / Decimal::from(level.consumed)).round_dp(1)
+    });
+
+    Some(ReorderSuggestion {
+        part_id: level.part_id,
+        component_type: level.component_type,
+        component_name: level.component_name,
+        quantity_available: level.quantity_available,
+        reorder_point: level.reorder_point,
+        target_stock: level.target_stock,
+        consumed: level.consumed,
+        on_order: level.on_order,
+        daily_consumption,
+        days_of_stock,
+        suggested_quantity,
+        estimated_cost: level.buy_in_price.map(|price| price * Decimal::from(suggested_quantity)),
+    })
+}
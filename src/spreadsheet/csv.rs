@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use ::csv::{ReaderBuilder, StringRecord, Trim, Writer};
+use anyhow::Result;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::{
+    ComponentCategory, ComponentSpecs, ComponentType, CreateBuyerRequest, CreateComponentRequest, CreateInventoryPartRequest, CsvEntity,
+    ImportRecord, ImportRow, ImportedPc, PcExport, PcStatus,
+};
+use crate::validation::ValidationErrors;
+use super::{header_key, parse_amount, parse_date};
+
+/// Joins several components of one type in a single cell, e.g. two RAM kits
+const LIST_SEPARATOR: &str = "; ";
+
+/// PC columns before the component columns; the import reads the same names
+const PC_COLUMNS: [&str; 17] = [
+    "id", "pc_name", "status", "build_date", "list_date", "sale_date", "days_listed", "days_held",
+    "buyer_name", "platform", "platform_reference", "intended_price", "actual_sale_price",
+    "total_cost", "profit", "profit_percentage", "notes",
+];
+
+/// One row per record, with a header row named after the record's JSON fields
+pub fn records_csv<T: Serialize>(records: &[T]) -> Result<Vec<u8>> {
+    let mut writer = Writer::from_writer(Vec::new());
+    for record in records {
+        writer.serialize(record)?;
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+/// One row per PC. Each component category gets a name column and a `_cost`
+/// column; several components of one category share the cells, separated by `; `.
+pub fn pcs_csv(pcs: &[PcExport], categories: &[ComponentCategory]) -> Result<Vec<u8>> {
+    let mut writer = Writer::from_writer(Vec::new());
+
+    let mut header: Vec<String> = PC_COLUMNS.iter().map(|column| column.to_string()).collect();
+    for category in categories {
+        header.push(category.slug.to_string());
+        header.push(format!("{}_cost", category.slug));
+    }
+    writer.write_record(&header)?;
+
+    for export in pcs {
+        let pc = &export.pc;
+        let mut record = vec![
+            pc.id.to_string(),
+            pc.pc_name.clone(),
+            pc.status.to_string(),
+            optional(pc.build_date),
+            optional(pc.list_date),
+            optional(pc.sale_date),
+            optional(pc.days_listed),
+            optional(pc.days_held),
+            export.buyer_name.clone().unwrap_or_default(),
+            pc.platform.clone().unwrap_or_default(),
+            pc.platform_reference.clone().unwrap_or_default(),
+            optional(pc.intended_price),
+            optional(pc.actual_sale_price),
+            optional(pc.total_cost),
+            optional(pc.profit),
+            optional(pc.profit_percentage),
+            pc.notes.clone().unwrap_or_default(),
+        ];
+        for category in categories {
+            let components: Vec<_> = export
+                .components
+                .iter()
+                .filter(|component| component.component_type == category.slug)
+                .collect();
+            record.push(join(components.iter().map(|component| component.component_name.clone())));
+            record.push(join(components.iter().map(|component| component.cost.to_string())));
+        }
+        writer.write_record(&record)?;
+    }
+
+    Ok(writer.into_inner()?)
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn join(values: impl Iterator<Item = String>) -> String {
+    values.collect::<Vec<_>>().join(LIST_SEPARATOR)
+}
+
+/// A data row with its header, looked up by column name
+struct CsvRecord<'a> {
+    columns: &'a HashMap<String, usize>,
+    /// Name columns that have a matching `_cost` column, in header order
+    component_columns: &'a [String],
+    record: &'a StringRecord,
+}
+
+impl CsvRecord<'_> {
+    fn text(&self, column: &str) -> Option<String> {
+        self.columns
+            .get(column)
+            .and_then(|&index| self.record.get(index))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    }
+
+    fn amount(&self, column: &str) -> Result<Option<Decimal>, String> {
+        self.text(column).map_or(Ok(None), |text| parse_amount(column, &text))
+    }
+
+    fn date(&self, column: &str) -> Result<Option<NaiveDate>, String> {
+        self.text(column).map_or(Ok(None), |text| parse_date(column, &text))
+    }
+}
+
+/// Reads a CSV file laid out like the matching export. Columns the import
+/// doesn't use, such as `id` or `total_cost`, are ignored.
+pub fn read_csv(entity: CsvEntity, bytes: &[u8]) -> Result<Vec<ImportRow>, ValidationErrors> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(bytes);
+
+    let header: Vec<String> = reader
+        .headers()
+        .map_err(|error| invalid_csv(format!("The header row could not be read: {}", error)))?
+        .iter()
+        .map(header_key)
+        .collect();
+    let columns: HashMap<String, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(index, column)| (column.clone(), index))
+        .collect();
+    // Component categories are whatever the file has a `<category>` and `<category>_cost` column for
+    let component_columns: Vec<String> = header
+        .iter()
+        .filter(|column| columns.contains_key(&format!("{}_cost", column)))
+        .cloned()
+        .collect();
+
+    let required: &[&str] = match entity {
+        CsvEntity::Pcs => &["pc_name"],
+        CsvEntity::Inventory => &["component_type", "component_name"],
+        CsvEntity::Buyers => &["name"],
+    };
+    if let Some(missing) = required.iter().find(|column| !columns.contains_key(**column)) {
+        return Err(invalid_csv(format!("The file has no '{}' column", missing)));
+    }
+
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(error) => {
+                rows.push(ImportRow {
+                    sheet: entity.as_str().to_string(),
+                    row: error.position().map_or(0, |position| position.line() as u32),
+                    name: None,
+                    record: Err(error.to_string()),
+                });
+                continue;
+            }
+        };
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+
+        let row = CsvRecord {
+            columns: &columns,
+            component_columns: &component_columns,
+            record: &record,
+        };
+        let (name, record) = match entity {
+            CsvEntity::Pcs => (row.text("pc_name"), read_pc(&row).map(ImportRecord::Pc)),
+            CsvEntity::Inventory => (row.text("component_name"), read_part(&row).map(ImportRecord::Part)),
+            CsvEntity::Buyers => (row.text("name"), Ok(ImportRecord::Buyer(read_buyer(&row)))),
+        };
+        rows.push(ImportRow {
+            sheet: entity.as_str().to_string(),
+            row: record_line(&row),
+            name,
+            record,
+        });
+    }
+
+    Ok(rows)
+}
+
+fn invalid_csv(message: impl Into<String>) -> ValidationErrors {
+    ValidationErrors::single("file", "invalid_csv", message)
+}
+
+fn record_line(row: &CsvRecord<'_>) -> u32 {
+    row.record.position().map_or(0, |position| position.line() as u32)
+}
+
+fn read_part(row: &CsvRecord<'_>) -> Result<CreateInventoryPartRequest, String> {
+    let quantity_available = row
+        .text("quantity_available")
+        .map(|text| {
+            text.parse::<i32>()
+                .map_err(|_| format!("quantity_available: '{}' is not a whole number", text))
+        })
+        .transpose()?;
+
+    Ok(CreateInventoryPartRequest {
+        component_type: ComponentType::new(&row.text("component_type").unwrap_or_default()),
+        component_name: row.text("component_name").unwrap_or_default(),
+        buy_in_price: row.amount("buy_in_price")?,
+        typical_sell_price: row.amount("typical_sell_price")?,
+        quantity_available,
+        reorder_point: None,
+        target_stock: None,
+        notes: row.text("notes"),
+        purchase_link: row.text("purchase_link"),
+    })
+}
+
+fn read_buyer(row: &CsvRecord<'_>) -> CreateBuyerRequest {
+    CreateBuyerRequest {
+        name: row.text("name").unwrap_or_default(),
+        contact: row.text("contact"),
+        email: row.text("email"),
+        phone: row.text("phone"),
+    }
+}
+
+fn read_pc(row: &CsvRecord<'_>) -> Result<ImportedPc, String> {
+    let mut components = Vec::new();
+    for column in row.component_columns {
+        let cost_column = format!("{}_cost", column);
+        let names = split(row.text(column));
+        let costs = split(row.text(&cost_column));
+        if names.len() != costs.len() {
+            return Err(format!(
+                "{}: {} names but {} costs in {}",
+                column,
+                names.len(),
+                costs.len(),
+                cost_column
+            ));
+        }
+
+        for (component_name, cost) in names.into_iter().zip(costs) {
+            components.push(CreateComponentRequest {
+                component_type: ComponentType::new(column),
+                component_name,
+                cost: parse_amount(&cost_column, &cost)?,
+                notes: None,
+                inventory_part_id: None,
+                specs: ComponentSpecs::default(),
+            });
+        }
+    }
+
+    Ok(ImportedPc {
+        pc_name: row.text("pc_name").unwrap_or_default(),
+        build_date: row.date("build_date")?,
+        list_date: row.date("list_date")?,
+        sale_date: row.date("sale_date")?,
+        intended_price: row.amount("intended_price")?,
+        actual_sale_price: row.amount("actual_sale_price")?,
+        platform: row.text("platform"),
+        platform_reference: row.text("platform_reference"),
+        notes: row.text("notes"),
+        // Every other status follows from the dates
+        archived: row
+            .text("status")
+            .is_some_and(|status| status.eq_ignore_ascii_case(PcStatus::Archived.as_str())),
+        buyer: row.text("buyer_name").map(|name| CreateBuyerRequest {
+            name,
+            contact: None,
+            email: None,
+            phone: None,
+        }),
+        components,
+    })
+}
+
+fn split(cell: Option<String>) -> Vec<String> {
+    cell.map(|cell| {
+        cell.split(LIST_SEPARATOR.trim())
+            .map(|value| value.trim().to_string())
+            .collect()
+    })
+    .unwrap_or_default()
+}
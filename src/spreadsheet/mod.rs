@@ -0,0 +1,95 @@
+//! Moving data in and out as files: the original PC Sales Tracker workbook
+//! and CSV. The sheets and columns below are the layout the tracker was kept
+//! in before this app existed.
+
+pub mod csv;
+pub mod xlsx;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+pub const PRICE_GUIDE: &str = "PRICE GUIDE";
+pub const MAIN_TRACKER: &str = "MAIN TRACKER";
+pub const MONTHLY_SUMMARY: &str = "MONTHLY SUMMARY";
+
+/// A component's name column in MAIN TRACKER; its cost is in "`<name>` Cost".
+/// Older copies of the tracker use the shorter aliases.
+pub struct ComponentColumn {
+    /// Category slug; Storage 1 and Storage 2 are both `storage`
+    pub slug: &'static str,
+    pub names: &'static [&'static str],
+}
+
+pub const COMPONENT_COLUMNS: [ComponentColumn; 10] = [
+    ComponentColumn { slug: "cpu", names: &["CPU"] },
+    ComponentColumn { slug: "gpu", names: &["GPU"] },
+    ComponentColumn { slug: "motherboard", names: &["Motherboard", "MB"] },
+    ComponentColumn { slug: "ram", names: &["RAM"] },
+    ComponentColumn { slug: "storage", names: &["Storage 1"] },
+    ComponentColumn { slug: "storage", names: &["Storage 2"] },
+    ComponentColumn { slug: "psu", names: &["PSU"] },
+    ComponentColumn { slug: "case", names: &["Case"] },
+    ComponentColumn { slug: "cpu_cooler", names: &["CPU Cooler", "Cooler"] },
+    ComponentColumn { slug: "other", names: &["Additional Parts"] },
+];
+
+/// Headers are matched ignoring case and any parenthesised unit, so
+/// "Buy In (kr)" and "Platform (Finn/Other)" match "buy in" and "platform"
+pub fn header_key(header: &str) -> String {
+    let header = header.split('(').next().unwrap_or_default();
+    header
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Amounts were typed by hand, e.g. "12 500 kr", "1 299,50 kr" or "999,-".
+/// Spaces group thousands and a comma before the last one or two digits is the
+/// decimal separator; any other comma is too ambiguous to guess at.
+pub fn parse_amount(column: &str, text: &str) -> Result<Option<Decimal>, String> {
+    let not_an_amount = || format!("{}: '{}' is not an amount", column, text.trim());
+
+    let mut cleaned: String = text
+        .replace("kr", "")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if let Some(whole) = cleaned.strip_suffix(",-") {
+        cleaned = whole.to_string();
+    }
+    if cleaned.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some((whole, fraction)) = cleaned.split_once(',') {
+        let is_decimal_comma = !whole.contains('.')
+            && (1..=2).contains(&fraction.len())
+            && fraction.chars().all(|c| c.is_ascii_digit());
+        if !is_decimal_comma {
+            return Err(not_an_amount());
+        }
+        cleaned = format!("{}.{}", whole, fraction);
+    }
+
+    cleaned
+        .parse::<Decimal>()
+        .map(|amount| Some(amount.round_dp(2)))
+        .map_err(|_| not_an_amount())
+}
+
+/// Dates typed as text, day first as in the tracker or ISO
+pub fn parse_date(column: &str, text: &str) -> Result<Option<NaiveDate>, String> {
+    const FORMATS: [&str; 4] = ["%d-%m-%Y", "%Y-%m-%d", "%d.%m.%Y", "%d/%m/%Y"];
+
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .map(Some)
+        .ok_or_else(|| format!("{}: '{}' is not a date (expected DD-MM-YYYY)", column, text))
+}
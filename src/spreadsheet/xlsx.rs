@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use calamine::{open_workbook_from_rs, Data, DataType, Range, Reader, Xlsx};
+use chrono::{Datelike, NaiveDate};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet, XlsxError};
+
+use crate::models::{
+    ComponentSpecs, ComponentType, CreateBuyerRequest, CreateComponentRequest, CreateInventoryPartRequest, ImportRecord, ImportRow,
+    ImportedPc, InventoryPart, MonthlySummary, PcExport,
+};
+use crate::validation::ValidationErrors;
+use super::{header_key, parse_amount, parse_date, COMPONENT_COLUMNS, MAIN_TRACKER, MONTHLY_SUMMARY, PRICE_GUIDE};
+
+/// How far down a sheet to look for its header row, past any title rows
+const HEADER_SEARCH_ROWS: usize = 10;
+
+/// A sheet's header row and the data rows below it
+struct Sheet {
+    name: String,
+    columns: HashMap<String, usize>,
+    /// Spreadsheet row number of the first data row
+    first_row: u32,
+    rows: Vec<Vec<Data>>,
+}
+
+impl Sheet {
+    fn has(&self, column: &str) -> bool {
+        self.columns.contains_key(&header_key(column))
+    }
+
+    fn cell<'a>(&self, row: &'a [Data], column: &str) -> Option<&'a Data> {
+        self.columns
+            .get(&header_key(column))
+            .and_then(|&index| row.get(index))
+    }
+
+    fn text(&self, row: &[Data], column: &str) -> Option<String> {
+        cell_text(self.cell(row, column)?)
+    }
+
+    fn amount(&self, row: &[Data], column: &str) -> Result<Option<Decimal>, String> {
+        match self.cell(row, column) {
+            Some(Data::Int(value)) => Ok(Some(Decimal::from(*value))),
+            Some(Data::Float(value)) => Decimal::try_from(*value)
+                .map(|amount| Some(amount.round_dp(2)))
+                .map_err(|_| format!("{}: {} is not an amount", column, value)),
+            Some(cell) => match cell_text(cell) {
+                Some(text) => parse_amount(column, &text),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn date(&self, row: &[Data], column: &str) -> Result<Option<NaiveDate>, String> {
+        match self.cell(row, column) {
+            Some(Data::String(text)) => parse_date(column, text),
+            Some(cell @ (Data::DateTime(_) | Data::DateTimeIso(_) | Data::Int(_) | Data::Float(_))) => cell
+                .as_date()
+                .map(Some)
+                .ok_or_else(|| format!("{}: {} is not a date", column, cell)),
+            _ => Ok(None),
+        }
+    }
+
+    fn data_rows(&self) -> impl Iterator<Item = (u32, &[Data])> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| (self.first_row + index as u32, row.as_slice()))
+    }
+}
+
+/// Reads PRICE GUIDE and MAIN TRACKER into import rows, parts first. Sheets are
+/// found by their headers rather than their names, as the Python importer did.
+pub fn read_workbook(bytes: &[u8]) -> Result<Vec<ImportRow>, ValidationErrors> {
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes))
+        .map_err(|error| invalid_workbook(format!("The file is not a readable .xlsx workbook: {}", error)))?;
+
+    let mut price_guide = None;
+    let mut main_tracker = None;
+    for name in workbook.sheet_names() {
+        let range = workbook
+            .worksheet_range(&name)
+            .map_err(|error| invalid_workbook(format!("Sheet '{}' could not be read: {}", name, error)))?;
+        let Some(sheet) = find_header(name, &range) else {
+            continue;
+        };
+
+        if price_guide.is_none() && sheet.has("Component Type") && sheet.has("Buy In") {
+            price_guide = Some(sheet);
+        } else if main_tracker.is_none() && sheet.has("PC ID") && sheet.has("Build Date") {
+            main_tracker = Some(sheet);
+        }
+    }
+
+    if price_guide.is_none() && main_tracker.is_none() {
+        return Err(invalid_workbook(
+            "No PRICE GUIDE or MAIN TRACKER sheet found; expected a 'Component Type' or 'PC ID' header",
+        ));
+    }
+
+    let mut rows = Vec::new();
+    if let Some(sheet) = &price_guide {
+        rows.extend(read_price_guide(sheet));
+    }
+    if let Some(sheet) = &main_tracker {
+        rows.extend(read_main_tracker(sheet));
+    }
+
+    Ok(rows)
+}
+
+fn invalid_workbook(message: impl Into<String>) -> ValidationErrors {
+    ValidationErrors::single("file", "invalid_workbook", message)
+}
+
+/// Finds the first row that looks like a header, i.e. one of the sheets' key columns
+fn find_header(name: String, range: &Range<Data>) -> Option<Sheet> {
+    let (start_row, _) = range.start()?;
+
+    range
+        .rows()
+        .take(HEADER_SEARCH_ROWS)
+        .position(|row| {
+            row.iter().any(|cell| {
+                cell_text(cell).is_some_and(|text| matches!(header_key(&text).as_str(), "component type" | "pc id"))
+            })
+        })
+        .map(|header_index| {
+            let mut rows = range.rows().skip(header_index);
+            let columns = rows
+                .next()
+                .unwrap_or_default()
+                .iter()
+                .enumerate()
+                .filter_map(|(index, cell)| Some((header_key(&cell_text(cell)?), index)))
+                .collect();
+
+            Sheet {
+                name,
+                columns,
+                first_row: start_row + header_index as u32 + 2,
+                rows: rows.map(<[Data]>::to_vec).collect(),
+            }
+        })
+}
+
+fn cell_text(cell: &Data) -> Option<String> {
+    let text = match cell {
+        Data::Empty | Data::Error(_) => return None,
+        Data::String(text) => text.trim().to_string(),
+        // Whole numbers typed into text columns, e.g. a PC ID of 12
+        Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", *value as i64),
+        cell => cell.to_string(),
+    };
+
+    (!text.is_empty()).then_some(text)
+}
+
+fn read_price_guide(sheet: &Sheet) -> Vec<ImportRow> {
+    sheet
+        .data_rows()
+        .filter_map(|(row_number, row)| {
+            // Rows without both a type and a name are blank or section headings
+            let component_type = ComponentType::new(&sheet.text(row, "Component Type")?);
+            let component_name = sheet.text(row, "Component")?;
+
+            Some(ImportRow {
+                sheet: sheet.name.clone(),
+                row: row_number,
+                name: Some(component_name.clone()),
+                record: read_part(sheet, row, component_type, component_name).map(ImportRecord::Part),
+            })
+        })
+        .collect()
+}
+
+fn read_part(
+    sheet: &Sheet,
+    row: &[Data],
+    component_type: ComponentType,
+    component_name: String,
+) -> Result<CreateInventoryPartRequest, String> {
+    let notes = sheet.text(row, "Notes");
+
+    // The tracker had no stock column; parts noted as incoming weren't in hand yet
+    let quantity_available = match sheet.amount(row, "Quantity")? {
+        Some(quantity) => Some(
+            i32::try_from(quantity)
+                .ok()
+                .filter(|_| quantity.fract().is_zero())
+                .ok_or_else(|| format!("Quantity: {} is not a whole number", quantity))?,
+        ),
+        None if notes.as_deref().is_some_and(|notes| notes.to_lowercase().contains("incoming")) => Some(0),
+        None => Some(1),
+    };
+
+    Ok(CreateInventoryPartRequest {
+        component_type,
+        component_name,
+        buy_in_price: sheet.amount(row, "Buy In")?,
+        typical_sell_price: sheet.amount(row, "Typical Sell Price")?,
+        quantity_available,
+        reorder_point: None,
+        target_stock: None,
+        notes,
+        purchase_link: sheet.text(row, "Link"),
+    })
+}
+
+fn read_main_tracker(sheet: &Sheet) -> Vec<ImportRow> {
+    sheet
+        .data_rows()
+        .filter_map(|(row_number, row)| {
+            let pc_name = sheet.text(row, "PC ID")?;
+
+            Some(ImportRow {
+                sheet: sheet.name.clone(),
+                row: row_number,
+                name: Some(pc_name.clone()),
+                record: read_pc(sheet, row, pc_name).map(ImportRecord::Pc),
+            })
+        })
+        .collect()
+}
+
+fn read_pc(sheet: &Sheet, row: &[Data], pc_name: String) -> Result<ImportedPc, String> {
+    let buyer = sheet.text(row, "Buyer Name").map(|name| CreateBuyerRequest {
+        name,
+        contact: sheet.text(row, "Buyer Contact"),
+        email: None,
+        phone: None,
+    });
+
+    let mut components = Vec::new();
+    for column in &COMPONENT_COLUMNS {
+        let Some(name) = column
+            .names
+            .iter()
+            .find(|name| sheet.has(name) && sheet.has(&format!("{} Cost", name)))
+        else {
+            continue;
+        };
+
+        // Unused slots are left blank or costed at zero
+        let component_name = sheet.text(row, name);
+        let cost = sheet.amount(row, &format!("{} Cost", name))?;
+        if let (Some(component_name), Some(cost)) = (component_name, cost) {
+            if cost > Decimal::ZERO {
+                components.push(CreateComponentRequest {
+                    component_type: ComponentType::new(column.slug),
+                    component_name,
+                    cost: Some(cost),
+                    notes: None,
+                    inventory_part_id: None,
+                    specs: ComponentSpecs::default(),
+                });
+            }
+        }
+    }
+
+    Ok(ImportedPc {
+        pc_name,
+        build_date: sheet.date(row, "Build Date")?,
+        list_date: sheet.date(row, "List Date")?,
+        sale_date: sheet.date(row, "Sale Date")?,
+        intended_price: sheet.amount(row, "Intended Price")?,
+        actual_sale_price: sheet.amount(row, "Actual Sale Price")?,
+        platform: sheet.text(row, "Platform"),
+        platform_reference: None,
+        notes: sheet.text(row, "Notes"),
+        archived: false,
+        buyer,
+        components,
+    })
+}
+
+/// MAIN TRACKER columns before the component columns
+const TRACKER_COLUMNS: [&str; 11] = [
+    "PC ID", "Build Date", "List Date", "Sale Date", "Days Listed", "Days Held", "Buyer Name",
+    "Buyer Contact", "Platform (Finn/Other)", "Intended Price", "Actual Sale Price",
+];
+
+/// MAIN TRACKER columns after the component columns
+const TRACKER_TOTAL_COLUMNS: [&str; 4] = ["Total Cost", "Profit", "Profit %", "Notes"];
+
+const PRICE_GUIDE_COLUMNS: [&str; 7] = [
+    "Component Type", "Component", "Buy In (kr)", "Typical Sell Price (kr)", "Quantity", "Notes", "Link",
+];
+
+const MONTHLY_SUMMARY_COLUMNS: [&str; 6] = [
+    "Month", "PCs Sold", "Total Sales (kr)", "Total Profit (kr)", "Avg Days Held", "Avg Profit Margin (%)",
+];
+
+/// Several components of one type share a cell, e.g. two RAM kits
+const LIST_SEPARATOR: &str = "; ";
+
+/// One cell of an exported row; empty values are left blank
+enum Cell {
+    Text(Option<String>),
+    Count(Option<i64>),
+    Amount(Option<Decimal>),
+    Percent(Option<Decimal>),
+    Decimal(Option<Decimal>),
+    Date(Option<NaiveDate>),
+    Month(NaiveDate),
+}
+
+/// Number formats shared by every sheet, as the tracker had them
+struct Formats {
+    header: Format,
+    amount: Format,
+    percent: Format,
+    decimal: Format,
+    date: Format,
+    month: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        Formats {
+            header: Format::new().set_bold(),
+            amount: Format::new().set_num_format("#,##0.00 \"kr\""),
+            percent: Format::new().set_num_format("0.00\"%\""),
+            decimal: Format::new().set_num_format("0.0"),
+            date: Format::new().set_num_format("dd-mm-yyyy"),
+            month: Format::new().set_num_format("mmmm yyyy"),
+        }
+    }
+}
+
+/// Writes live PCs, inventory and the monthly summary in the layout of the
+/// original tracker, so the file opens like the old spreadsheet and can be
+/// read back by [`read_workbook`]. Several components of one type are joined
+/// in one name cell with their costs summed, as the tracker had one cost per
+/// slot.
+pub fn tracker_workbook(pcs: &[PcExport], parts: &[InventoryPart], summary: &[MonthlySummary]) -> anyhow::Result<Vec<u8>> {
+    let formats = Formats::new();
+    let mut workbook = Workbook::new();
+
+    let mut header: Vec<String> = TRACKER_COLUMNS.iter().map(|column| column.to_string()).collect();
+    for column in &COMPONENT_COLUMNS {
+        header.push(column.names[0].to_string());
+        header.push(format!("{} Cost", column.names[0]));
+    }
+    header.extend(TRACKER_TOTAL_COLUMNS.iter().map(|column| column.to_string()));
+    let rows = pcs.iter().map(tracker_row);
+    write_sheet(workbook.add_worksheet(), MAIN_TRACKER, &header, rows, &formats)?;
+
+    let header: Vec<String> = PRICE_GUIDE_COLUMNS.iter().map(|column| column.to_string()).collect();
+    let rows = parts.iter().map(|part| {
+        vec![
+            Cell::Text(Some(part.component_type.as_str().replace('_', " ").to_uppercase())),
+            Cell::Text(Some(part.component_name.clone())),
+            Cell::Amount(part.buy_in_price),
+            Cell::Amount(part.typical_sell_price),
+            Cell::Count(Some(i64::from(part.quantity_available))),
+            Cell::Text(part.notes.clone()),
+            Cell::Text(part.purchase_link.clone()),
+        ]
+    });
+    write_sheet(workbook.add_worksheet(), PRICE_GUIDE, &header, rows, &formats)?;
+
+    let header: Vec<String> = MONTHLY_SUMMARY_COLUMNS.iter().map(|column| column.to_string()).collect();
+    let rows = summary.iter().map(|month| {
+        // Months come back from the database as "YYYY-MM"
+        let month_cell = NaiveDate::parse_from_str(&format!("{}-01", month.month_year), "%Y-%m-%d")
+            .map_or_else(|_| Cell::Text(Some(month.month_year.clone())), Cell::Month);
+        vec![
+            month_cell,
+            Cell::Count(Some(month.pcs_sold)),
+            Cell::Amount(month.total_sales),
+            Cell::Amount(month.total_profit),
+            Cell::Decimal(month.average_days_held),
+            Cell::Percent(month.average_profit_margin),
+        ]
+    });
+    write_sheet(workbook.add_worksheet(), MONTHLY_SUMMARY, &header, rows, &formats)?;
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+fn tracker_row(export: &PcExport) -> Vec<Cell> {
+    let pc = &export.pc;
+    let mut row = vec![
+        Cell::Text(Some(pc.pc_name.clone())),
+        Cell::Date(pc.build_date),
+        Cell::Date(pc.list_date),
+        Cell::Date(pc.sale_date),
+        Cell::Count(pc.days_listed.map(i64::from)),
+        Cell::Count(pc.days_held.map(i64::from)),
+        Cell::Text(export.buyer_name.clone()),
+        Cell::Text(export.buyer_contact.clone()),
+        Cell::Text(pc.platform.clone()),
+        Cell::Amount(pc.intended_price),
+        Cell::Amount(pc.actual_sale_price),
+    ];
+    for (index, column) in COMPONENT_COLUMNS.iter().enumerate() {
+        // Categories without a column of their own go under Additional Parts
+        let components: Vec<_> = export
+            .components
+            .iter()
+            .filter(|component| tracker_slug(&component.component_type) == column.slug)
+            .collect();
+
+        // Columns sharing a category, like Storage 1 and 2, take one component
+        // each and the last of them takes the rest
+        let earlier = COMPONENT_COLUMNS[..index].iter().filter(|other| other.slug == column.slug).count();
+        let last = !COMPONENT_COLUMNS[index + 1..].iter().any(|other| other.slug == column.slug);
+        let end = if last { components.len() } else { (earlier + 1).min(components.len()) };
+        let components = components.get(earlier..end).unwrap_or_default();
+        if components.is_empty() {
+            row.extend([Cell::Text(None), Cell::Amount(None)]);
+            continue;
+        }
+
+        let names: Vec<&str> = components.iter().map(|component| component.component_name.as_str()).collect();
+        row.push(Cell::Text(Some(names.join(LIST_SEPARATOR))));
+        row.push(Cell::Amount(Some(components.iter().map(|component| component.cost).sum())));
+    }
+    row.extend([
+        Cell::Amount(pc.total_cost),
+        Cell::Amount(pc.profit),
+        Cell::Percent(pc.profit_percentage),
+        Cell::Text(pc.notes.clone()),
+    ]);
+
+    row
+}
+
+fn tracker_slug(component_type: &ComponentType) -> &str {
+    let slug = component_type.as_str();
+    if COMPONENT_COLUMNS.iter().any(|column| column.slug == slug) {
+        slug
+    } else {
+        "other"
+    }
+}
+
+/// A bold header row frozen above the data rows, with columns sized to fit
+fn write_sheet(
+    worksheet: &mut Worksheet,
+    name: &str,
+    header: &[String],
+    rows: impl Iterator<Item = Vec<Cell>>,
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    worksheet.set_name(name)?;
+    for (col, title) in header.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, title, &formats.header)?;
+    }
+
+    for (index, cells) in rows.enumerate() {
+        let row = index as u32 + 1;
+        for (col, cell) in cells.into_iter().enumerate() {
+            write_cell(worksheet, row, col as u16, cell, formats)?;
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofit();
+
+    Ok(())
+}
+
+fn write_cell(worksheet: &mut Worksheet, row: u32, col: u16, cell: Cell, formats: &Formats) -> Result<(), XlsxError> {
+    match cell {
+        Cell::Text(Some(text)) => {
+            worksheet.write_string(row, col, text)?;
+        }
+        Cell::Count(Some(count)) => {
+            worksheet.write_number(row, col, count as f64)?;
+        }
+        Cell::Amount(Some(amount)) => write_decimal(worksheet, row, col, amount, &formats.amount)?,
+        Cell::Percent(Some(percent)) => write_decimal(worksheet, row, col, percent, &formats.percent)?,
+        Cell::Decimal(Some(value)) => write_decimal(worksheet, row, col, value, &formats.decimal)?,
+        Cell::Date(Some(date)) => write_date(worksheet, row, col, date, &formats.date)?,
+        Cell::Month(month) => write_date(worksheet, row, col, month, &formats.month)?,
+        Cell::Text(None) | Cell::Count(None) | Cell::Amount(None) | Cell::Percent(None) | Cell::Decimal(None)
+        | Cell::Date(None) => {}
+    }
+
+    Ok(())
+}
+
+fn write_decimal(worksheet: &mut Worksheet, row: u32, col: u16, value: Decimal, format: &Format) -> Result<(), XlsxError> {
+    // Excel keeps every number as a double; amounts to the øre survive that unchanged
+    worksheet.write_number_with_format(row, col, value.to_f64().unwrap_or_default(), format)?;
+
+    Ok(())
+}
+
+fn write_date(worksheet: &mut Worksheet, row: u32, col: u16, date: NaiveDate, format: &Format) -> Result<(), XlsxError> {
+    let date = ExcelDateTime::from_ymd(date.year() as u16, date.month() as u8, date.day() as u8)?;
+    worksheet.write_datetime_with_format(row, col, &date, format)?;
+
+    Ok(())
+}
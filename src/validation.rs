@@ -0,0 +1,626 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::models::{
+    ChangePasswordRequest, ComponentSpecs, CreateBuyerRequest, CreateComponentCategoryRequest, CreateComponentRequest,
+    CreateInventoryPartRequest, CreatePcRequest, CreatePurchaseOrderLineRequest, CreatePurchaseOrderRequest, CreateSerializedItemRequest, CreateStockMovementRequest, CreateSupplierRequest, CreateUserRequest, DiscountReportQuery, ImportedPc, ListingSettingsRequest, LowStockQuery, MergeBuyersRequest, PriceSuggestionQuery, ReceiveLineRequest, ReceivePurchaseOrderRequest, ReorderReportQuery, SerialLookupQuery,
+    SellPcRequest, StaleListingQuery, StockMovementType, UpdateBuyerRequest, UpdateComponentCategoryRequest, UpdateComponentRequest,
+    UpdateInventoryPartRequest, UpdatePcRequest, UpdatePurchaseOrderRequest, UpdateSerializedItemRequest, UpdateSupplierRequest, UpdateUserRequest,
+};
+use crate::reorder::MAX_CONSUMPTION_DAYS;
+
+/// Largest amount that fits the `DECIMAL(10,2)` money columns
+const MAX_AMOUNT: i64 = 100_000_000;
+
+const MIN_PASSWORD_LEN: usize = 8;
+const MAX_PASSWORD_LEN: usize = 128;
+
+/// A single problem with one field of a request
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Every problem found in a request, reported together as one 422
+#[derive(Debug, Default)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn single(field: impl Into<String>, code: &'static str, message: impl Into<String>) -> Self {
+        let mut errors = ValidationErrors::default();
+        errors.add(field, code, message);
+        errors
+    }
+
+    pub fn add(&mut self, field: impl Into<String>, code: &'static str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            field: field.into(),
+            code,
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    pub fn required_text(&mut self, field: &str, value: &str, max_len: usize) {
+        if value.trim().is_empty() {
+            self.add(field, "required", format!("{} must not be empty", field));
+        } else {
+            self.max_len(field, value, max_len);
+        }
+    }
+
+    pub fn optional_text(&mut self, field: &str, value: Option<&str>, max_len: usize) {
+        match value {
+            Some(value) if value.trim().is_empty() => {
+                self.add(field, "required", format!("{} must not be blank", field))
+            }
+            Some(value) => self.max_len(field, value, max_len),
+            None => {}
+        }
+    }
+
+    fn max_len(&mut self, field: &str, value: &str, max_len: usize) {
+        if value.chars().count() > max_len {
+            self.add(field, "too_long", format!("{} must be at most {} characters", field, max_len));
+        }
+    }
+
+    pub fn amount(&mut self, field: &str, value: Option<Decimal>) {
+        let Some(value) = value else {
+            return;
+        };
+
+        if value < Decimal::ZERO {
+            self.add(field, "negative", format!("{} must not be negative", field));
+        } else if value >= Decimal::from(MAX_AMOUNT) {
+            self.add(field, "too_large", format!("{} must be less than {}", field, MAX_AMOUNT));
+        }
+    }
+
+    pub fn quantity(&mut self, field: &str, value: Option<i32>) {
+        if value.is_some_and(|value| value < 0) {
+            self.add(field, "negative", format!("{} must not be negative", field));
+        }
+    }
+
+    pub fn slot_limit(&mut self, field: &str, value: Option<i32>) {
+        if value.is_some_and(|value| value < 1) {
+            self.add(field, "too_small", format!("{} must be at least 1", field));
+        }
+    }
+
+    /// A reorder point and target stock level, when given together, must not contradict each other
+    pub fn stock_levels(&mut self, reorder_point: Option<i32>, target_stock: Option<i32>) {
+        self.quantity("reorder_point", reorder_point);
+        self.quantity("target_stock", target_stock);
+        if let (Some(reorder_point), Some(target_stock)) = (reorder_point, target_stock) {
+            if target_stock < reorder_point {
+                self.add("target_stock", "below_reorder_point", "target_stock must not be below reorder_point");
+            }
+        }
+    }
+
+    pub fn positive(&mut self, field: &str, value: Option<i32>) {
+        if value.is_some_and(|value| value < 1) {
+            self.add(field, "not_positive", format!("{} must be greater than 0", field));
+        }
+    }
+
+    pub fn date_order(&mut self, field: &str, earlier: Option<NaiveDate>, later: Option<NaiveDate>, earlier_field: &str) {
+        if let (Some(earlier), Some(later)) = (earlier, later) {
+            if later < earlier {
+                self.add(field, "before_earlier_date", format!("{} must not be before {}", field, earlier_field));
+            }
+        }
+    }
+
+    pub fn email(&mut self, field: &str, value: Option<&str>) {
+        let Some(value) = value else {
+            return;
+        };
+
+        if !is_valid_email(value) {
+            self.add(field, "invalid_email", format!("{} is not a valid email address", field));
+        } else {
+            self.max_len(field, value, 255);
+        }
+    }
+
+    pub fn phone(&mut self, field: &str, value: Option<&str>) {
+        let Some(value) = value else {
+            return;
+        };
+
+        if !is_valid_phone(value) {
+            self.add(field, "invalid_phone", format!("{} is not a valid phone number", field));
+        } else {
+            self.max_len(field, value, 20);
+        }
+    }
+
+    pub fn username(&mut self, field: &str, value: &str) {
+        let valid_chars = value
+            .trim()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+
+        if !valid_chars {
+            self.add(field, "invalid_username", format!("{} may only contain letters, digits, '.', '_' and '-'", field));
+        } else {
+            self.required_text(field, value, 50);
+        }
+    }
+
+    pub fn password(&mut self, field: &str, value: &str) {
+        let len = value.chars().count();
+        if len < MIN_PASSWORD_LEN {
+            self.add(field, "too_short", format!("{} must be at least {} characters", field, MIN_PASSWORD_LEN));
+        } else if len > MAX_PASSWORD_LEN {
+            self.add(field, "too_long", format!("{} must be at most {} characters", field, MAX_PASSWORD_LEN));
+        }
+    }
+
+    /// Adds a nested request's errors under a prefix such as `components[2]`
+    pub fn nested(&mut self, prefix: &str, result: Result<(), ValidationErrors>) {
+        if let Err(nested) = result {
+            for error in nested.errors {
+                self.errors.push(FieldError {
+                    field: format!("{}.{}", prefix, error.field),
+                    ..error
+                });
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.errors.iter().map(|error| error.message.as_str()).collect();
+        write!(f, "Validation failed: {}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+fn is_valid_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !domain.contains('@')
+        && !value.chars().any(char::is_whitespace)
+        && domain.split('.').count() >= 2
+        && domain.split('.').all(|label| !label.is_empty())
+}
+
+/// Accepts digits with optional leading `+` and spaces, dashes or parentheses as separators
+fn is_valid_phone(value: &str) -> bool {
+    let digits = value.trim_start_matches('+');
+    let digit_count = digits.chars().filter(char::is_ascii_digit).count();
+
+    digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, ' ' | '-' | '(' | ')'))
+        && (6..=15).contains(&digit_count)
+}
+
+/// Checks a request payload before it reaches the database
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+impl Validate for CreatePcRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("pc_name", &self.pc_name, 255);
+        errors.amount("intended_price", self.intended_price);
+        for (index, component) in self.components.iter().enumerate() {
+            errors.nested(&format!("components[{}]", index), component.validate());
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdatePcRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("pc_name", self.pc_name.as_deref(), 255);
+        errors.amount("intended_price", self.intended_price);
+        errors.optional_text("price_change_reason", self.price_change_reason.as_deref(), 1000);
+        errors.date_order("list_date", self.build_date, self.list_date, "build_date");
+        errors.into_result()
+    }
+}
+
+impl Validate for SellPcRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.amount("actual_sale_price", Some(self.actual_sale_price));
+        errors.optional_text("platform", self.platform.as_deref(), 50);
+        errors.optional_text("platform_reference", self.platform_reference.as_deref(), 100);
+        errors.into_result()
+    }
+}
+
+impl Validate for ImportedPc {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("pc_name", &self.pc_name, 255);
+        errors.amount("intended_price", self.intended_price);
+        errors.amount("actual_sale_price", self.actual_sale_price);
+        errors.optional_text("platform", self.platform.as_deref(), 50);
+        errors.optional_text("platform_reference", self.platform_reference.as_deref(), 100);
+        errors.date_order("list_date", self.build_date, self.list_date, "build_date");
+        errors.date_order("sale_date", self.build_date, self.sale_date, "build_date");
+        if let Some(buyer) = &self.buyer {
+            errors.nested("buyer", buyer.validate());
+        }
+        for (index, component) in self.components.iter().enumerate() {
+            errors.nested(&format!("components[{}]", index), component.validate());
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateComponentRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("component_type", self.component_type.as_str(), 50);
+        errors.required_text("component_name", &self.component_name, 255);
+        errors.amount("cost", self.cost);
+        if self.cost.is_none() && self.inventory_part_id.is_none() {
+            errors.add("cost", "required", "cost is required unless inventory_part_id is given");
+        }
+        errors.nested("specs", self.specs.validate());
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateComponentRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("component_name", self.component_name.as_deref(), 255);
+        errors.amount("cost", self.cost);
+        if let Some(specs) = &self.specs {
+            errors.nested("specs", specs.validate());
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for PriceSuggestionQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.target_margin.is_some_and(|margin| margin <= -Decimal::ONE_HUNDRED) {
+            errors.add("target_margin", "too_small", "target_margin must be greater than -100");
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for StaleListingQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.quantity("days", self.days);
+        errors.into_result()
+    }
+}
+
+impl Validate for ListingSettingsRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.quantity("stale_after_days", Some(self.stale_after_days));
+        if let Some(percent) = self.price_drop_percent {
+            if percent <= Decimal::ZERO || percent >= Decimal::ONE_HUNDRED {
+                errors.add("price_drop_percent", "out_of_range", "price_drop_percent must be between 0 and 100");
+            }
+        }
+        errors.positive("price_drop_interval_days", self.price_drop_interval_days);
+        if self.price_drop_percent.is_some() != self.price_drop_interval_days.is_some() {
+            errors.add(
+                "price_drop_interval_days",
+                "required",
+                "price_drop_percent and price_drop_interval_days must be given together",
+            );
+        }
+        if self.min_margin <= -Decimal::ONE_HUNDRED || self.min_margin >= Decimal::from(1000) {
+            errors.add("min_margin", "out_of_range", "min_margin must be greater than -100 and less than 1000");
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for ComponentSpecs {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("socket", self.socket.as_deref(), 30);
+        errors.optional_text("memory_type", self.memory_type.as_deref(), 30);
+        errors.optional_text("form_factor", self.form_factor.as_deref(), 30);
+        errors.positive("tdp_watts", self.tdp_watts);
+        errors.positive("wattage", self.wattage);
+        errors.positive("length_mm", self.length_mm);
+        errors.positive("max_gpu_length_mm", self.max_gpu_length_mm);
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateComponentCategoryRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("slug", self.slug.as_str(), 50);
+        errors.required_text("name", &self.name, 100);
+        errors.slot_limit("max_per_pc", self.max_per_pc);
+        errors.stock_levels(self.reorder_point, self.target_stock);
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateComponentCategoryRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("name", self.name.as_deref(), 100);
+        errors.slot_limit("max_per_pc", self.max_per_pc);
+        errors.stock_levels(self.reorder_point, self.target_stock);
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateInventoryPartRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("component_type", self.component_type.as_str(), 50);
+        errors.required_text("component_name", &self.component_name, 255);
+        errors.amount("buy_in_price", self.buy_in_price);
+        errors.amount("typical_sell_price", self.typical_sell_price);
+        errors.quantity("quantity_available", self.quantity_available);
+        errors.stock_levels(self.reorder_point, self.target_stock);
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateInventoryPartRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("component_name", self.component_name.as_deref(), 255);
+        errors.amount("buy_in_price", self.buy_in_price);
+        errors.amount("typical_sell_price", self.typical_sell_price);
+        errors.optional_text("price_change_reason", self.price_change_reason.as_deref(), 1000);
+        errors.quantity("quantity_available", self.quantity_available);
+        errors.stock_levels(self.reorder_point, self.target_stock);
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateStockMovementRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        match self.movement_type {
+            StockMovementType::Consume => errors.add(
+                "movement_type",
+                "invalid_movement_type",
+                "Parts are consumed by adding them to a PC as components",
+            ),
+            StockMovementType::Adjust if self.quantity == 0 => {
+                errors.add("quantity", "required", "quantity must not be 0")
+            }
+            StockMovementType::Adjust => {}
+            _ => errors.positive("quantity", Some(self.quantity)),
+        }
+        errors.amount("unit_cost", self.unit_cost);
+        errors.optional_text("reason", self.reason.as_deref(), 1000);
+        errors.optional_text("reference", self.reference.as_deref(), 255);
+        errors.into_result()
+    }
+}
+
+impl Validate for DiscountReportQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.date_order("to", self.from, self.to, "from");
+        errors.into_result()
+    }
+}
+
+impl Validate for LowStockQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.quantity("threshold", self.threshold);
+        errors.into_result()
+    }
+}
+
+impl Validate for ReorderReportQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.positive("days", self.days);
+        if self.days.is_some_and(|days| days > MAX_CONSUMPTION_DAYS) {
+            errors.add("days", "too_large", format!("days must be at most {}", MAX_CONSUMPTION_DAYS));
+        }
+        errors.positive("cover_days", self.cover_days);
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateBuyerRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("name", &self.name, 255);
+        errors.optional_text("contact", self.contact.as_deref(), 50);
+        errors.email("email", self.email.as_deref());
+        errors.phone("phone", self.phone.as_deref());
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateBuyerRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("name", self.name.as_deref(), 255);
+        errors.optional_text("contact", self.contact.as_deref(), 50);
+        errors.email("email", self.email.as_deref());
+        errors.phone("phone", self.phone.as_deref());
+        errors.into_result()
+    }
+}
+
+impl Validate for MergeBuyersRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.duplicate_ids.is_empty() {
+            errors.add("duplicate_ids", "required", "duplicate_ids must list at least one buyer");
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateSupplierRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("name", &self.name, 255);
+        errors.optional_text("website", self.website.as_deref(), 1000);
+        errors.optional_text("contact", self.contact.as_deref(), 255);
+        errors.email("email", self.email.as_deref());
+        errors.phone("phone", self.phone.as_deref());
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateSupplierRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("name", self.name.as_deref(), 255);
+        errors.optional_text("website", self.website.as_deref(), 1000);
+        errors.optional_text("contact", self.contact.as_deref(), 255);
+        errors.email("email", self.email.as_deref());
+        errors.phone("phone", self.phone.as_deref());
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateSerializedItemRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("serial_number", &self.serial_number, 100);
+        errors.optional_text("acquired_from", self.acquired_from.as_deref(), 255);
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateSerializedItemRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("serial_number", self.serial_number.as_deref(), 100);
+        errors.optional_text("acquired_from", self.acquired_from.as_deref(), 255);
+        errors.into_result()
+    }
+}
+
+impl Validate for SerialLookupQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.required_text("serial", &self.serial, 100);
+        errors.into_result()
+    }
+}
+
+impl Validate for CreatePurchaseOrderRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("order_reference", self.order_reference.as_deref(), 100);
+        errors.date_order("expected_date", self.order_date, self.expected_date, "order_date");
+        errors.amount("shipping_cost", self.shipping_cost);
+        errors.amount("customs_cost", self.customs_cost);
+        if self.lines.is_empty() {
+            errors.add("lines", "required", "lines must list at least one part");
+        }
+        for (index, line) in self.lines.iter().enumerate() {
+            errors.nested(&format!("lines[{}]", index), line.validate());
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for CreatePurchaseOrderLineRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.positive("quantity", Some(self.quantity));
+        errors.amount("unit_cost", Some(self.unit_cost));
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdatePurchaseOrderRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("order_reference", self.order_reference.as_deref(), 100);
+        errors.amount("shipping_cost", self.shipping_cost);
+        errors.amount("customs_cost", self.customs_cost);
+        errors.into_result()
+    }
+}
+
+impl Validate for ReceivePurchaseOrderRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        if self.lines.is_empty() {
+            errors.add("lines", "required", "lines must list at least one received line");
+        }
+        for (index, line) in self.lines.iter().enumerate() {
+            errors.nested(&format!("lines[{}]", index), line.validate());
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for ReceiveLineRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.positive("quantity", Some(self.quantity));
+        errors.into_result()
+    }
+}
+
+impl Validate for CreateUserRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.username("username", &self.username);
+        errors.optional_text("display_name", self.display_name.as_deref(), 255);
+        errors.password("password", &self.password);
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateUserRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.optional_text("display_name", self.display_name.as_deref(), 255);
+        errors.into_result()
+    }
+}
+
+impl Validate for ChangePasswordRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        errors.password("new_password", &self.new_password);
+        errors.into_result()
+    }
+}